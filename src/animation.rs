@@ -0,0 +1,99 @@
+use crate::dbug;
+use iced::widget::image::Handle;
+use image::AnimationDecoder;
+use image::codecs::png::PngDecoder;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/**
+Shortest frame delay we honour, some files declare zero which would spin the CPU
+*/
+const MIN_FRAME_DELAY: Duration = Duration::from_millis(20);
+
+/**
+Decoded animated preview for a single emoji
+- Only the hovered emoji is ever decoded, so at most one of these is alive at a time
+*/
+pub struct Animation {
+    pub emoji: String, // Emoji this animation belongs to
+    frames: Vec<(Handle, Duration)>,
+    current: usize,
+    frame_started: Instant,
+}
+
+impl Animation {
+    /**
+    Load the APNG sidecar asset for an emoji, if there is one
+    - Assets are named after the emoji's codepoints, e.g. 1f600.png or 2764-fe0f.png
+    @param dir: Directory containing the animation assets
+    @param emoji: The emoji glyph
+    @return Option<Animation>: Decoded animation, None if there is no usable asset
+    */
+    pub fn load(dir: &Path, emoji: &str) -> Option<Animation> {
+        let path = dir.join(asset_name(emoji));
+        let file = std::fs::File::open(&path).ok()?;
+        let decoder = PngDecoder::new(std::io::BufReader::new(file)).ok()?;
+        if !decoder.is_apng() {
+            dbug!("{} is not animated, using the static glyph", path.display());
+            return None;
+        }
+
+        let mut frames = Vec::new();
+        for frame in decoder.apng().into_frames() {
+            let frame = frame.ok()?;
+            let (numerator, denominator) = frame.delay().numer_denom_ms();
+            let delay = Duration::from_millis(u64::from(numerator / denominator.max(1)));
+            let buffer = frame.into_buffer();
+            let handle = Handle::from_pixels(buffer.width(), buffer.height(), buffer.into_raw());
+            frames.push((handle, delay.max(MIN_FRAME_DELAY)));
+        }
+
+        if frames.is_empty() {
+            return None;
+        }
+        dbug!(
+            "Loaded {} animation frames from {}",
+            frames.len(),
+            path.display()
+        );
+
+        Some(Animation {
+            emoji: emoji.to_string(),
+            frames,
+            current: 0,
+            frame_started: Instant::now(),
+        })
+    }
+
+    /**
+    Move to the next frame once the current one has been shown long enough
+    @param &mut self: Mutable self reference
+    @param now: Current time
+    */
+    pub fn advance(&mut self, now: Instant) {
+        let delay = self.frames[self.current].1;
+        if now.duration_since(self.frame_started) >= delay {
+            self.current = (self.current + 1) % self.frames.len();
+            self.frame_started = now;
+        }
+    }
+
+    /**
+    Image handle for the frame currently being shown
+    @param &self: Self reference
+    @return Handle: Current frame
+    */
+    pub fn frame(&self) -> Handle {
+        self.frames[self.current].0.clone()
+    }
+}
+
+/**
+File name of the animation asset for an emoji
+@param emoji: The emoji glyph
+@return String: Lowercase hex codepoints joined by dashes, with a .png extension
+*/
+fn asset_name(emoji: &str) -> String {
+    let codepoints: Vec<String> = emoji.chars().map(|c| format!("{:x}", c as u32)).collect();
+    format!("{}.png", codepoints.join("-"))
+}
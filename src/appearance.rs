@@ -0,0 +1,40 @@
+use std::process::Command;
+
+/**
+Light or dark preference reported by the desktop
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Appearance {
+    Light,
+    Dark,
+}
+
+/**
+Ask the desktop whether it prefers a light or dark appearance
+- Iced doesn't report appearance changes, so this gets polled
+- Reads GTK_THEME first, then the GNOME color-scheme setting, which most Linux desktops follow
+@return Option<Appearance>: The preference, None if the platform can't tell us
+*/
+pub fn detect() -> Option<Appearance> {
+    if let Ok(gtk_theme) = std::env::var("GTK_THEME") {
+        return Some(if gtk_theme.ends_with(":dark") {
+            Appearance::Dark
+        } else {
+            Appearance::Light
+        });
+    }
+
+    let output = Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let scheme = String::from_utf8_lossy(&output.stdout);
+    Some(if scheme.contains("dark") {
+        Appearance::Dark
+    } else {
+        Appearance::Light
+    })
+}
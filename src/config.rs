@@ -0,0 +1,723 @@
+use crate::format::Representation;
+use crate::paths;
+use crate::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/**
+Order in which the grid is displayed when nothing else dictates it
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortMode {
+    #[default]
+    Dataset, // Order of entries in the data file
+    Alphabetical, // By primary keyword
+    Usage,        // Most copied first
+}
+
+impl SortMode {
+    /**
+    Cycle to the next sort mode
+    @param self: Current sort mode
+    @return SortMode: The mode that follows it
+    */
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Dataset => SortMode::Alphabetical,
+            SortMode::Alphabetical => SortMode::Usage,
+            SortMode::Usage => SortMode::Dataset,
+        }
+    }
+}
+
+/**
+How the grid is browsed, one long scrollable list or discrete pages
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScrollMode {
+    #[default]
+    Scroll,
+    Paged,
+}
+
+/**
+Which emoji fields a search query is matched against
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchScope {
+    Keywords,
+    Category,
+    #[default]
+    Both,
+}
+
+impl SearchScope {
+    /**
+    Cycle to the next search scope
+    @param self: Current scope
+    @return SearchScope: The scope that follows it
+    */
+    pub fn next(self) -> Self {
+        match self {
+            SearchScope::Both => SearchScope::Keywords,
+            SearchScope::Keywords => SearchScope::Category,
+            SearchScope::Category => SearchScope::Both,
+        }
+    }
+
+    /**
+    Check if keywords are searched in this scope
+    @param self: Current scope
+    @return bool: True if keywords are matched
+    */
+    pub fn includes_keywords(self) -> bool {
+        self != SearchScope::Category
+    }
+
+    /**
+    Check if the category is searched in this scope
+    @param self: Current scope
+    @return bool: True if the category is matched
+    */
+    pub fn includes_category(self) -> bool {
+        self != SearchScope::Keywords
+    }
+}
+
+/**
+Feedback given when an emoji is copied
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feedback {
+    None,
+    #[default]
+    Visual, // Brief border flash on the copied cell
+    Sound, // System beep, needs the sound feature
+    Both,
+}
+
+impl Feedback {
+    /**
+    Check if the copied cell should flash
+    @param self: Current feedback setting
+    @return bool: True for Visual and Both
+    */
+    pub fn visual(self) -> bool {
+        matches!(self, Feedback::Visual | Feedback::Both)
+    }
+
+    /**
+    Check if a beep should play
+    @param self: Current feedback setting
+    @return bool: True for Sound and Both
+    */
+    pub fn sound(self) -> bool {
+        matches!(self, Feedback::Sound | Feedback::Both)
+    }
+}
+
+/**
+What clicking an emoji does
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickMode {
+    #[default]
+    SingleCopy, // Click copies straight away
+    DoubleCopy, // Click previews in the detail panel, double-click copies
+}
+
+/**
+Which selection copies are written to
+- X11 and Wayland have both a CLIPBOARD (ctrl+v) and a PRIMARY (middle-click) selection
+- PRIMARY needs the primary-selection feature and wl-copy or xclip installed
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClipboardTarget {
+    #[default]
+    Clipboard,
+    Primary,
+    Both,
+}
+
+impl ClipboardTarget {
+    /**
+    Check if copies go to the regular clipboard
+    @param self: Current target
+    @return bool: True for Clipboard and Both
+    */
+    pub fn clipboard(self) -> bool {
+        matches!(self, ClipboardTarget::Clipboard | ClipboardTarget::Both)
+    }
+
+    /**
+    Check if copies go to the primary selection
+    @param self: Current target
+    @return bool: True for Primary and Both
+    */
+    pub fn primary(self) -> bool {
+        matches!(self, ClipboardTarget::Primary | ClipboardTarget::Both)
+    }
+}
+
+/**
+How emojis are laid out
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    #[default]
+    Grid, // Glyphs only, as many per row as fit
+    List, // One emoji per row with its name and category
+}
+
+impl Layout {
+    /**
+    Switch to the other layout
+    @param self: Current layout
+    @return Layout: The other layout
+    */
+    pub fn toggle(self) -> Self {
+        match self {
+            Layout::Grid => Layout::List,
+            Layout::List => Layout::Grid,
+        }
+    }
+}
+
+/**
+Categories to keep out of the app entirely, compared case-insensitively
+*/
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CategoryFilter {
+    pub allow: Vec<String>, // Only these categories, empty for all of them
+    pub deny: Vec<String>,  // Never these categories, wins over allow
+}
+
+/**
+Emoji font sizes of the sections above the grid, each one the grid's size when unset
+*/
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct SectionSizes {
+    pub favorites: Option<u16>,
+    pub recents: Option<u16>,
+    pub suggestions: Option<u16>, // The "Often used with" section
+}
+
+impl CategoryFilter {
+    /**
+    Check if the filter leaves everything in
+    @param &self: Self reference
+    @return bool: True if neither list has entries
+    */
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /**
+    Check if a category makes it through the filter
+    @param &self: Self reference
+    @param category: Category to check
+    @return bool: True if emojis in this category should be shown
+    */
+    pub fn includes(&self, category: &str) -> bool {
+        let listed = |list: &[String]| list.iter().any(|c| c.eq_ignore_ascii_case(category));
+        (self.allow.is_empty() || listed(&self.allow)) && !listed(&self.deny)
+    }
+}
+
+/**
+Where the search box sits relative to the results
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchPosition {
+    #[default]
+    Top,
+    Bottom, // Results above the search box, for launchers anchored to the bottom of the screen
+}
+
+/**
+What happens to the window once an emoji has been handed to the clipboard
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PostCopy {
+    #[default]
+    Stay,
+    Close,      // Close right away, for launcher style use
+    CloseDelay, // Close after a moment, long enough to see the confirmation
+}
+
+/**
+Where the window opens on the monitor with the cursor
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Anchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    Center,
+}
+
+/**
+Where an emoji sits vertically in its cell, for fonts whose glyphs don't center on their own
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EmojiAlign {
+    Top,
+    #[default]
+    Center,
+    Bottom,
+}
+
+/**
+How entries of a dataset are compared when dropping duplicates
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupKey {
+    Strict, // Only identical glyphs are duplicates
+    #[default]
+    Normalized, // Glyphs are compared without presentation selectors, so "❤" and "❤️" are duplicates
+}
+
+/**
+Shape of the hover and selection background behind an emoji
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellShape {
+    #[default]
+    Square, // Slightly rounded corners
+    Circle, // Cells are as wide as they are tall, so fewer fit in a row
+}
+
+/**
+Color scheme of the window
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    Light,
+    #[default]
+    Dark,
+    System, // Follow the desktop's light or dark preference, dark where it can't be read
+}
+
+impl ThemeMode {
+    /**
+    Every theme mode, in the order the settings overlay lists them
+    */
+    pub const ALL: [ThemeMode; 3] = [ThemeMode::Light, ThemeMode::Dark, ThemeMode::System];
+
+    /**
+    Name shown in the settings overlay
+    @param self: Theme mode
+    @return &str: Label
+    */
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeMode::Light => "Light",
+            ThemeMode::Dark => "Dark",
+            ThemeMode::System => "System",
+        }
+    }
+}
+
+/**
+Which set of emojis the grid shows
+*/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum View {
+    #[default]
+    All,
+    Recents,
+    Favorites,
+    Frequent,         // Recents and favorites merged, see merge_frequent
+    Category(String), // Written as {"category": "faces"} in the config
+}
+
+/**
+Longest separator allowed between emojis in a multi-copy
+*/
+const MAX_SEPARATOR_CHARS: usize = 8;
+
+/**
+Emoji font sizes a section above the grid can use
+*/
+const SECTION_SIZE_RANGE: std::ops::RangeInclusive<u16> = 12..=128;
+
+/**
+RGB color written as a "#rrggbb" hex string in the config
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct HexColor {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let hex = value.strip_prefix('#').unwrap_or(&value);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(format!("invalid color {:?}, expected \"#rrggbb\"", value));
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+        Ok(HexColor {
+            r: channel(0),
+            g: channel(2),
+            b: channel(4),
+        })
+    }
+}
+
+/**
+User configuration, every field falls back to its default when missing from the file
+*/
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub sort_mode: SortMode,
+    pub scroll_mode: ScrollMode,
+    pub layout: Layout, // Until toggled, the last layout is remembered after that
+    pub rows_per_page: usize, // Only used in paged mode
+    pub min_columns: usize, // Fewest grid columns, however narrow the window
+    pub max_columns: usize, // Most grid columns, however wide the window
+    pub retain_query: bool, // Keep the search query after copying the top result
+    pub escape_clears_query: bool, // Escape clears the search before it closes the window, off to close right away
+    pub search_follows_view: bool, // Search within recents, favorites or a category while one is open
+    pub data_path: Option<PathBuf>, // External data file replacing the embedded data
+    pub data_url: Option<String>, // Dataset fetched on first run and cached, needs the remote-data feature
+    pub dedup_key: DedupKey, // How duplicate entries in a data file are recognized, the first one is kept
+    pub font_path: Option<PathBuf>, // Emoji font file replacing the bundled font
+    pub search_scope: SearchScope,
+    pub search_position: SearchPosition,
+    pub case_sensitive: bool, // Tell "Cat" from "cat" when searching, accents never matter
+    pub whole_word_below: usize, // Queries shorter than this rank whole words first, e.g. "ok" over "book", 0 to turn off
+    pub min_query_len: usize, // Shorter queries don't filter yet, 0 to filter from the first character
+    pub synonyms: HashMap<String, Vec<String>>, // Extra words a search term also looks for
+    pub feedback: Feedback,
+    pub start_view: View,
+    pub category_filter: CategoryFilter, // Categories hidden everywhere, including search
+    pub section_sizes: SectionSizes, // Emoji size of favorites, recents and suggestions, e.g. larger
+    pub section_gap: u16, // Pixels between the sections above the grid and the grid itself
+    pub merge_frequent: bool, // Show one frequent view instead of separate recents and favorites
+    pub click_mode: ClickMode,
+    pub clipboard_target: ClipboardTarget,
+    pub copy_as: Representation, // What clicks, search and random picks copy, e.g. "escape" for source code
+    pub multi_copy_separator: String, // Between emojis when copying a selection, e.g. " " or "\n"
+    pub multi_copy_prefix: String, // Before a copied selection
+    pub multi_copy_suffix: String, // After a copied selection
+    pub copy_suffix: String,     // After every copy, once per selection, e.g. " "
+    pub keybindings: HashMap<String, Vec<String>>, // Action name to key combos, e.g. "quit": ["ctrl+q"]
+    pub theme: ThemeMode, // Until one is picked in the settings, which is remembered after that
+    pub hover_color: Option<HexColor>, // Cell hover highlight, derived from the theme when unset
+    pub corner_radius: f32, // Rounds the window corners, 0 for square
+    pub emoji_align: EmojiAlign,
+    pub cell_shape: CellShape, // Square or circular highlight behind hovered and selected emojis
+    pub animations: bool,      // Slide the results in when the search or view changes
+    pub emoji_offset: f32, // Nudges emojis down in their cells by this many pixels, negative for up
+    pub max_height_share: f32, // Tallest the window gets, as a share of the monitor's work area, 1 for all of it
+    pub anchor: Option<Anchor>, // Corner or center the window opens at, up to the window manager when unset
+    pub placeholder: Option<String>, // Shown in cells until the emoji font loads, null for a spinner
+    pub idle_timeout_secs: Option<u64>, // Close the window after this long without input, off when unset
+    pub nav_repeat_ms: Option<u64>, // Least time between focus moves while an arrow key is held, the OS key repeat when unset
+    pub post_copy: PostCopy, // Stay open, close, or close after a moment once something is copied
+    pub confirm_clears: bool, // Ask before clearing recents, favorites or tags
+    pub log_copy_queries: bool, // Log the search query each copy came from, off for privacy
+    pub fresh_start: bool, // Start without recents, favorites, layout and theme from earlier runs, like --fresh
+    pub show_codepoints: bool, // Show codepoints and Unicode version in the detail panel
+    pub show_source: bool, // Show which data file an emoji came from in the detail panel
+    pub log_scrollback: usize, // Log lines kept for the log viewer, the oldest go first
+    #[cfg(feature = "animated-preview")]
+    pub animation_dir: Option<PathBuf>, // Directory of APNG previews
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            sort_mode: SortMode::default(),
+            scroll_mode: ScrollMode::default(),
+            layout: Layout::default(),
+            rows_per_page: 4,
+            min_columns: 1,
+            max_columns: 16,
+            retain_query: false,
+            escape_clears_query: true,
+            search_follows_view: true,
+            data_path: None,
+            data_url: None,
+            dedup_key: DedupKey::default(),
+            font_path: None,
+            search_scope: SearchScope::default(),
+            search_position: SearchPosition::default(),
+            case_sensitive: false,
+            whole_word_below: 3,
+            min_query_len: 0,
+            synonyms: HashMap::new(),
+            feedback: Feedback::default(),
+            start_view: View::default(),
+            category_filter: CategoryFilter::default(),
+            section_sizes: SectionSizes::default(),
+            section_gap: 20,
+            merge_frequent: false,
+            click_mode: ClickMode::default(),
+            clipboard_target: ClipboardTarget::default(),
+            copy_as: Representation::default(),
+            multi_copy_separator: String::new(),
+            multi_copy_prefix: String::new(),
+            multi_copy_suffix: String::new(),
+            copy_suffix: String::new(),
+            keybindings: HashMap::new(),
+            theme: ThemeMode::default(),
+            hover_color: None,
+            corner_radius: 0.0,
+            emoji_align: EmojiAlign::default(),
+            cell_shape: CellShape::default(),
+            animations: false,
+            emoji_offset: 0.0,
+            max_height_share: 0.9,
+            anchor: None,
+            placeholder: Some("⏳".to_string()),
+            idle_timeout_secs: None,
+            nav_repeat_ms: None,
+            post_copy: PostCopy::default(),
+            confirm_clears: true,
+            log_copy_queries: false,
+            fresh_start: false,
+            show_codepoints: false,
+            show_source: false,
+            log_scrollback: 1000,
+            #[cfg(feature = "animated-preview")]
+            animation_dir: None,
+        }
+    }
+}
+
+impl Config {
+    /**
+    Load the configuration file, falling back to defaults if it is missing or invalid
+    @return Config: Loaded configuration
+    */
+    pub fn load() -> Self {
+        let path = config_path();
+
+        let contents = match std::fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(_) => {
+                info!("No config found at {}, using defaults", path.display());
+                return Config::default();
+            }
+        };
+
+        match serde_json::from_str::<Config>(&contents) {
+            Ok(config) => {
+                info!("Loaded config from {}", path.display());
+                config.validate()
+            }
+            Err(e) => {
+                warn!("Failed to parse {}: {}, using defaults", path.display(), e);
+                Config::default()
+            }
+        }
+    }
+}
+
+impl Config {
+    /**
+    External data file to load, if any
+    - An explicit data_path wins, otherwise a configured data_url is read from its cache
+    @param &self: Self reference
+    @return Option<PathBuf>: Data file, None for the embedded data
+    */
+    pub fn data_source(&self) -> Option<PathBuf> {
+        if self.data_path.is_some() {
+            return self.data_path.clone();
+        }
+        self.data_url.as_ref().map(|_| data_cache_path())
+    }
+
+    /**
+    Replace values that parse but make no sense with their defaults, and normalize the rest
+    @param self: Parsed configuration
+    @return Config: Configuration safe to use
+    */
+    fn validate(mut self) -> Self {
+        if !self.corner_radius.is_finite() || self.corner_radius < 0.0 {
+            warn!(
+                "corner_radius {} is not a size, using square corners",
+                self.corner_radius
+            );
+            self.corner_radius = 0.0;
+        }
+        // Past half a cell the emoji would leave its cell
+        let max_offset = f32::from(crate::EMOJI_SIZE) / 2.0;
+        if !self.emoji_offset.is_finite() || self.emoji_offset.abs() > max_offset {
+            warn!(
+                "emoji_offset {} is more than {} pixels, using 0",
+                self.emoji_offset, max_offset
+            );
+            self.emoji_offset = 0.0;
+        }
+        for size in [
+            &mut self.section_sizes.favorites,
+            &mut self.section_sizes.recents,
+            &mut self.section_sizes.suggestions,
+        ] {
+            if size.is_some_and(|s| !SECTION_SIZE_RANGE.contains(&s)) {
+                warn!(
+                    "Section size {:?} is not between {} and {}, using the grid size",
+                    size,
+                    SECTION_SIZE_RANGE.start(),
+                    SECTION_SIZE_RANGE.end()
+                );
+                *size = None;
+            }
+        }
+        if self.log_scrollback == 0 {
+            warn!("log_scrollback has to keep at least one line, using 1000");
+            self.log_scrollback = 1000;
+        }
+        if !(self.max_height_share > 0.0 && self.max_height_share <= 1.0) {
+            warn!(
+                "max_height_share {} is not between 0 and 1, using 0.9",
+                self.max_height_share
+            );
+            self.max_height_share = 0.9;
+        }
+
+        // Synonyms are matched against the lowercased query
+        self.synonyms = self
+            .synonyms
+            .into_iter()
+            .map(|(word, synonyms)| {
+                let synonyms = synonyms.iter().map(|s| s.trim().to_lowercase()).collect();
+                (word.trim().to_lowercase(), synonyms)
+            })
+            .collect();
+
+        if self.multi_copy_separator.chars().count() > MAX_SEPARATOR_CHARS {
+            warn!(
+                "multi_copy_separator {:?} is longer than {} characters, using none",
+                self.multi_copy_separator, MAX_SEPARATOR_CHARS
+            );
+            self.multi_copy_separator = String::new();
+        }
+        if self.copy_suffix.chars().count() > MAX_SEPARATOR_CHARS {
+            warn!(
+                "copy_suffix {:?} is longer than {} characters, using none",
+                self.copy_suffix, MAX_SEPARATOR_CHARS
+            );
+            self.copy_suffix = String::new();
+        }
+        self
+    }
+}
+
+/**
+Location of the config file, in the platform's config directory
+@return PathBuf: Path to the config file
+*/
+fn config_path() -> PathBuf {
+    paths::config_dir().join("config.json")
+}
+
+/**
+Location of debug snapshots, next to the config file
+@return PathBuf: Path to the snapshot
+*/
+pub fn snapshot_path() -> PathBuf {
+    paths::config_dir().join("debug-snapshot.json")
+}
+
+/**
+Location of the dataset fetched from data_url, next to the config file
+@return PathBuf: Path to the cached dataset
+*/
+pub fn data_cache_path() -> PathBuf {
+    paths::config_dir().join("data-cache.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    An overly long multi-copy separator is dropped, short ones like a newline are kept
+    */
+    #[test]
+    fn long_separator_is_rejected() {
+        let newline = Config {
+            multi_copy_separator: "\n".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(newline.validate().multi_copy_separator, "\n");
+
+        let long = Config {
+            multi_copy_separator: "-".repeat(MAX_SEPARATOR_CHARS + 1),
+            ..Config::default()
+        };
+        assert_eq!(long.validate().multi_copy_separator, "");
+    }
+
+    /**
+    An allow list keeps only its categories, ignoring case
+    */
+    #[test]
+    fn category_allow_list_keeps_only_its_categories() {
+        let filter = CategoryFilter {
+            allow: vec!["Smileys".to_string(), "food".to_string()],
+            deny: Vec::new(),
+        };
+        assert!(filter.includes("Smileys"));
+        assert!(filter.includes("SMILEYS"));
+        assert!(filter.includes("Food"));
+        assert!(!filter.includes("Animals"));
+    }
+
+    /**
+    A deny list drops its categories, ignoring case, and keeps the rest
+    */
+    #[test]
+    fn category_deny_list_drops_its_categories() {
+        let filter = CategoryFilter {
+            allow: Vec::new(),
+            deny: vec!["Flags".to_string()],
+        };
+        assert!(!filter.includes("Flags"));
+        assert!(!filter.includes("flags"));
+        assert!(filter.includes("Smileys"));
+        assert!(CategoryFilter::default().includes("Flags"));
+    }
+
+    /**
+    A category on both lists is filtered out
+    */
+    #[test]
+    fn category_deny_wins_over_allow() {
+        let filter = CategoryFilter {
+            allow: vec!["Flags".to_string(), "Food".to_string()],
+            deny: vec!["FLAGS".to_string()],
+        };
+        assert!(!filter.includes("Flags"));
+        assert!(filter.includes("Food"));
+        assert!(!filter.includes("Smileys"));
+    }
+}
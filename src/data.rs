@@ -0,0 +1,784 @@
+use crate::config::{CategoryFilter, DedupKey};
+use crate::{fail, info, warn};
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::path::Path;
+
+/**
+Emoji data structure
+*/
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmojiData {
+    pub emoji: String,
+    pub keywords: String,
+    pub category: String,
+    #[serde(default)]
+    pub variants: Vec<String>, // Related glyphs, e.g. gendered or directional forms
+    #[serde(default)]
+    pub added_version: Option<String>, // Emoji version the glyph first appeared in, e.g. "15.1"
+    #[serde(default)]
+    pub unicode_version: Option<String>, // Unicode version the codepoints were introduced in, e.g. "6.0"
+    #[serde(default)]
+    pub seasonal: bool, // Tied to a holiday or season
+    #[serde(default)]
+    pub source: Option<String>, // Set where the entry came from, e.g. "custom", None for the embedded data
+}
+
+/**
+Small marker on a cell pointing out an emoji worth discovering
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Badge {
+    New,      // Added in the newest emoji version in the dataset
+    Seasonal, // Tied to a holiday or season
+}
+
+impl Badge {
+    /**
+    Marker drawn next to the glyph
+    @param self: Badge to draw
+    @return &'static str: Marker text
+    */
+    pub fn marker(self) -> &'static str {
+        match self {
+            Badge::New => "✦",
+            Badge::Seasonal => "❖",
+        }
+    }
+}
+
+impl EmojiData {
+    /**
+    Badge to show on this emoji, new wins over seasonal
+    @param &self: Self reference
+    @param newest: Newest emoji version in the dataset, see newest_version
+    @return Option<Badge>: Badge, None for most emojis
+    */
+    pub fn badge(&self, newest: Option<&str>) -> Option<Badge> {
+        if newest.is_some() && self.added_version.as_deref() == newest {
+            Some(Badge::New)
+        } else if self.seasonal {
+            Some(Badge::Seasonal)
+        } else {
+            None
+        }
+    }
+}
+
+/**
+Find the newest emoji version any emoji in the dataset was added in
+- Versions are compared number by number, so "15.1" is newer than "9.0"
+@param emojis: Loaded emoji data
+@return Option<String>: Newest version, None if the dataset has no versions
+*/
+pub fn newest_version(emojis: &[EmojiData]) -> Option<String> {
+    let numbers = |version: &str| -> Vec<u32> {
+        version
+            .split('.')
+            .map(|part| part.trim().parse().unwrap_or(0))
+            .collect()
+    };
+    emojis
+        .iter()
+        .filter_map(|emoji| emoji.added_version.as_deref())
+        .max_by_key(|version| numbers(version))
+        .map(str::to_string)
+}
+
+/**
+Newest data file version this build understands
+*/
+pub const SUPPORTED_VERSION: u32 = 1;
+
+/**
+Versioned data file, `{"version": N, "emojis": [...]}`
+- A bare array of emojis is still accepted and treated as version 1
+*/
+#[derive(Debug, Deserialize)]
+pub struct DataFile {
+    pub version: u32,
+    pub emojis: Vec<EmojiData>,
+}
+
+/**
+Source given to entries of an external data file that don't name one themselves
+*/
+const CUSTOM_SOURCE: &str = "custom";
+
+/**
+Presentation selectors, which pasted or copied glyphs may or may not carry
+*/
+const PRESENTATION_SELECTORS: [char; 2] = ['\u{FE0E}', '\u{FE0F}'];
+
+/**
+Lookup of emojis by glyph, built once when the data is loaded
+- Variants resolve to their base emoji
+- Keys have their presentation selectors stripped, so "❤" and "❤️" find the same entry
+*/
+#[derive(Debug, Default)]
+pub struct GlyphIndex {
+    positions: HashMap<String, usize>,
+}
+
+impl GlyphIndex {
+    /**
+    Index every emoji and variant in a dataset
+    - If a glyph appears more than once the first entry wins, matching a linear search
+    @param emojis: Loaded emoji data
+    @return GlyphIndex: Index into emojis
+    */
+    pub fn build(emojis: &[EmojiData]) -> Self {
+        let mut positions = HashMap::with_capacity(emojis.len());
+        for (position, emoji) in emojis.iter().enumerate() {
+            for glyph in std::iter::once(&emoji.emoji).chain(&emoji.variants) {
+                positions.entry(normalize_glyph(glyph)).or_insert(position);
+            }
+        }
+        GlyphIndex { positions }
+    }
+
+    /**
+    Find the position of a glyph in the dataset the index was built from
+    @param &self: Self reference
+    @param glyph: Emoji glyph, with or without presentation selectors
+    @return Option<usize>: Index into the dataset
+    */
+    pub fn position(&self, glyph: &str) -> Option<usize> {
+        self.positions.get(&normalize_glyph(glyph)).copied()
+    }
+
+    /**
+    Resolve a glyph back to its emoji data
+    @param &self: Self reference
+    @param emojis: The dataset the index was built from
+    @param glyph: Emoji glyph, with or without presentation selectors
+    @return Option<&EmojiData>: Keywords, category and variants of the glyph
+    */
+    pub fn lookup<'a>(&self, emojis: &'a [EmojiData], glyph: &str) -> Option<&'a EmojiData> {
+        emojis.get(self.position(glyph)?)
+    }
+}
+
+/**
+Strip presentation selectors from a glyph so differently encoded copies compare equal
+@param glyph: Emoji glyph
+@return String: Glyph without presentation selectors
+*/
+pub fn normalize_glyph(glyph: &str) -> String {
+    glyph
+        .chars()
+        .filter(|c| !PRESENTATION_SELECTORS.contains(c))
+        .collect()
+}
+
+/**
+Drop entries whose glyph repeats an earlier one, keeping the first
+- Each dropped entry is logged along with the form that was kept, selectors shown escaped
+@param emojis: Loaded emoji data
+@param key: Whether glyphs must match exactly or only without presentation selectors
+@return Vec<EmojiData>: Emoji data without duplicates, in dataset order
+*/
+pub fn dedup(emojis: Vec<EmojiData>, key: DedupKey) -> Vec<EmojiData> {
+    let mut first_seen: HashMap<String, usize> = HashMap::with_capacity(emojis.len());
+    let mut kept: Vec<EmojiData> = Vec::with_capacity(emojis.len());
+    for (position, emoji) in emojis.into_iter().enumerate() {
+        let glyph = match key {
+            DedupKey::Strict => emoji.emoji.clone(),
+            DedupKey::Normalized => normalize_glyph(&emoji.emoji),
+        };
+        match first_seen.get(&glyph) {
+            Some(&index) => info!(
+                "Dropped duplicate {:?} at entry {}, kept {:?}",
+                emoji.emoji,
+                position + 1,
+                kept[index].emoji
+            ),
+            None => {
+                first_seen.insert(glyph, kept.len());
+                kept.push(emoji);
+            }
+        }
+    }
+    kept
+}
+
+/**
+How many bytes of a data file are parsed between progress reports
+*/
+const PROGRESS_BYTES: usize = 64 * 1024;
+
+/**
+Errors that can occur while loading an external data file
+*/
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    Parse {
+        message: String,
+        line: usize,
+        column: usize,
+    },
+    Incompatible(u32), // Newer version that couldn't be parsed
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse {
+                message,
+                line,
+                column,
+            } => write!(f, "line {}, column {}: {}", line, column, message),
+            LoadError::Incompatible(version) => write!(
+                f,
+                "data file version {} is not supported (newest supported is {})",
+                version, SUPPORTED_VERSION
+            ),
+        }
+    }
+}
+
+/**
+Syntax an emoji data file is written in
+*/
+#[derive(Clone, Copy)]
+enum Syntax {
+    Json,
+    #[cfg(feature = "json5")]
+    Json5,
+}
+
+impl Syntax {
+    /**
+    Parse text in this syntax
+    @param self: Syntax to parse with
+    @param contents: Text to parse
+    @return Result<T, LoadError>: Parsed value, or the error with its position
+    */
+    fn parse<T: DeserializeOwned>(self, contents: &str) -> Result<T, LoadError> {
+        match self {
+            Syntax::Json => serde_json::from_str(contents).map_err(json_error),
+            #[cfg(feature = "json5")]
+            Syntax::Json5 => json5::from_str(contents).map_err(|e| match e {
+                json5::Error::Message { msg, location } => {
+                    let (line, column) = location.map_or((0, 0), |l| (l.line, l.column));
+                    LoadError::Parse {
+                        message: msg,
+                        line,
+                        column,
+                    }
+                }
+            }),
+        }
+    }
+
+    /**
+    Parse a whole data file document, reporting how much of it has been read
+    - Strict JSON is fed to the parser in chunks, so progress covers the parse itself
+    - JSON5 can only be parsed in one go, so its progress jumps straight to the end
+    @param self: Syntax to parse with
+    @param contents: Text to parse
+    @param progress: Called with (bytes read, total bytes) as the text is parsed
+    @return Result<serde_json::Value, LoadError>: Parsed document, or the error with its position
+    */
+    fn parse_document(
+        self,
+        contents: &str,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<serde_json::Value, LoadError> {
+        match self {
+            Syntax::Json => {
+                let reader = ProgressReader {
+                    remaining: contents.as_bytes(),
+                    total: contents.len(),
+                    progress,
+                };
+                serde_json::from_reader(BufReader::with_capacity(PROGRESS_BYTES, reader))
+                    .map_err(json_error)
+            }
+            #[cfg(feature = "json5")]
+            Syntax::Json5 => {
+                let document = self.parse(contents)?;
+                progress(contents.len(), contents.len());
+                Ok(document)
+            }
+        }
+    }
+}
+
+/**
+Turn a serde_json error into a parse error with its position
+@param e: Error from serde_json
+@return LoadError: Parse error
+*/
+fn json_error(e: serde_json::Error) -> LoadError {
+    LoadError::Parse {
+        message: e.to_string(),
+        line: e.line(),
+        column: e.column(),
+    }
+}
+
+/**
+Reader over text in memory that reports how far into it the parser has got
+*/
+struct ProgressReader<'a> {
+    remaining: &'a [u8], // Not handed to the parser yet
+    total: usize,        // Length of the whole text
+    progress: &'a mut dyn FnMut(usize, usize),
+}
+
+impl Read for ProgressReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.remaining.read(buf)?;
+        if read > 0 {
+            (self.progress)(self.total - self.remaining.len(), self.total);
+        }
+        Ok(read)
+    }
+}
+
+impl DataFile {
+    /**
+    Parse a data file in either the versioned or the bare array format
+    - Newer versions are parsed on a best-effort basis, and reported as incompatible if that fails
+    - Progress follows the parse of the document, converting it to emojis afterwards is quick
+    @param contents: File contents
+    @param syntax: Syntax the file is written in
+    @param progress: Called with (bytes read, total bytes) as the file is parsed
+    @return Result<DataFile, LoadError>: Parsed data file
+    */
+    fn parse(
+        contents: &str,
+        syntax: Syntax,
+        progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<DataFile, LoadError> {
+        let (bare, version, entries) = match syntax.parse_document(contents, progress)? {
+            serde_json::Value::Array(entries) => (true, 1, entries),
+            serde_json::Value::Object(mut fields) => {
+                let version = fields
+                    .get("version")
+                    .and_then(serde_json::Value::as_u64)
+                    .and_then(|version| u32::try_from(version).ok());
+                match (version, fields.remove("emojis")) {
+                    (Some(version), Some(serde_json::Value::Array(entries))) => {
+                        (false, version, entries)
+                    }
+                    _ => return Err(Self::explain(contents, syntax, false, version)),
+                }
+            }
+            _ => return Err(Self::explain(contents, syntax, false, None)),
+        };
+
+        if version > SUPPORTED_VERSION {
+            warn!(
+                "Data file version {} is newer than supported version {}, some fields may be ignored",
+                version, SUPPORTED_VERSION
+            );
+        }
+
+        let mut emojis = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match EmojiData::deserialize(entry) {
+                Ok(emoji) => emojis.push(emoji),
+                Err(_) => return Err(Self::explain(contents, syntax, bare, Some(version))),
+            }
+        }
+
+        Ok(DataFile { version, emojis })
+    }
+
+    /**
+    Work out why a data file couldn't be converted
+    - Newer versions are incompatible, anything else gets the typed parse error with its position
+    @param contents: File contents
+    @param syntax: Syntax the file is written in
+    @param bare: Whether the file is a bare array rather than the versioned format
+    @param version: Version declared by the file, if it got that far
+    @return LoadError: Error to report
+    */
+    fn explain(contents: &str, syntax: Syntax, bare: bool, version: Option<u32>) -> LoadError {
+        if let Some(version) = version.filter(|&version| version > SUPPORTED_VERSION) {
+            return LoadError::Incompatible(version);
+        }
+        let typed = if bare {
+            syntax.parse::<Vec<EmojiData>>(contents).map(drop)
+        } else {
+            syntax.parse::<DataFile>(contents).map(drop)
+        };
+        typed.err().unwrap_or(LoadError::Parse {
+            message: "unexpected data file layout".to_string(),
+            line: 0,
+            column: 0,
+        })
+    }
+}
+
+/**
+Load the emoji data, preferring an external data file when one is configured
+- Falls back to the embedded data if the external file can't be read or parsed
+- A file from a newer, incompatible version is an error, so the user finds out instead of silently getting the defaults
+- Duplicate entries in an external file are dropped, compared according to key
+- Entries of an external file without a source of their own get "custom" as their source
+@param path: Optional path to an external data file
+@param key: How duplicate entries are recognized
+@param progress: Called with (bytes read, total bytes) while an external file is parsed
+@return Result<Vec<EmojiData>, LoadError>: Parsed emoji data, or the incompatibility
+*/
+pub fn load(
+    path: Option<&Path>,
+    key: DedupKey,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<Vec<EmojiData>, LoadError> {
+    let Some(path) = path else {
+        return Ok(load_embedded());
+    };
+
+    match load_file(path, progress) {
+        Ok(emojis) => {
+            info!("Loaded {} emojis from {}", emojis.len(), path.display());
+            let packed = emojis
+                .iter()
+                .filter(|emoji| crate::format::split_graphemes(emoji.emoji.trim()).len() > 1)
+                .count();
+            if packed > 0 {
+                warn!(
+                    "{} entries in {} hold more than one emoji, run nicepick lint on it for details",
+                    packed,
+                    path.display()
+                );
+            }
+            let emojis = emojis
+                .into_iter()
+                .map(|mut emoji| {
+                    emoji
+                        .source
+                        .get_or_insert_with(|| CUSTOM_SOURCE.to_string());
+                    emoji
+                })
+                .collect();
+            Ok(dedup(emojis, key))
+        }
+        Err(e @ LoadError::Incompatible(_)) => {
+            fail!("Failed to load {}: {}", path.display(), e);
+            Err(e)
+        }
+        Err(e) => {
+            fail!(
+                "Failed to load {}: {}, using embedded data",
+                path.display(),
+                e
+            );
+            Ok(load_embedded())
+        }
+    }
+}
+
+/**
+Drop the emojis in categories the user has filtered out
+- Only logs when a filter is set, an empty one keeps everything without a word
+@param emojis: Full dataset
+@param filter: Category allow and deny lists
+@return Vec<EmojiData>: Emojis in the remaining categories, in dataset order
+*/
+pub fn filter_categories(emojis: &[EmojiData], filter: &CategoryFilter) -> Vec<EmojiData> {
+    let kept: Vec<EmojiData> = emojis
+        .iter()
+        .filter(|emoji| filter.includes(&emoji.category))
+        .cloned()
+        .collect();
+    if !filter.is_empty() {
+        info!(
+            "Category filter excluded {} of {} emojis",
+            emojis.len() - kept.len(),
+            emojis.len()
+        );
+    }
+    kept
+}
+
+/**
+Load and parse the embedded JSON emoji data
+- Always strict JSON, the embedded data is under our control
+@return Vec<EmojiData>: Parsed emoji data
+*/
+pub fn load_embedded() -> Vec<EmojiData> {
+    let json_data = include_str!("../data.json");
+    DataFile::parse(json_data, Syntax::Json, &mut |_, _| {})
+        .expect("Failed to parse data.json")
+        .emojis
+}
+
+/**
+Parse a strict JSON dataset that isn't on disk yet, e.g. a download
+@param contents: Data file contents
+@return Result<Vec<EmojiData>, LoadError>: Parsed emoji data
+*/
+#[cfg(feature = "remote-data")]
+pub fn parse_json(contents: &str) -> Result<Vec<EmojiData>, LoadError> {
+    DataFile::parse(contents, Syntax::Json, &mut |_, _| {}).map(|file| file.emojis)
+}
+
+/**
+Load and parse an external emoji data file
+- `.json5` files are parsed as JSON5, other files as strict JSON with a JSON5 retry if that fails
+- JSON5 support needs the `json5` feature
+@param path: Path to the data file
+@param progress: Called with (bytes read, total bytes) as the file is parsed
+@return Result<Vec<EmojiData>, LoadError>: Parsed emoji data
+*/
+pub fn load_file(
+    path: &Path,
+    progress: &mut dyn FnMut(usize, usize),
+) -> Result<Vec<EmojiData>, LoadError> {
+    let contents = std::fs::read_to_string(path).map_err(LoadError::Io)?;
+
+    #[cfg(feature = "json5")]
+    let file = if path.extension().is_some_and(|ext| ext == "json5") {
+        DataFile::parse(&contents, Syntax::Json5, progress)
+    } else {
+        // Strict JSON first, but hand-edited files often have comments or trailing commas
+        DataFile::parse(&contents, Syntax::Json, progress).or_else(
+            |strict_error| match strict_error {
+                LoadError::Parse { .. } => {
+                    DataFile::parse(&contents, Syntax::Json5, progress).map_err(|_| strict_error)
+                }
+                other => Err(other),
+            },
+        )
+    };
+
+    #[cfg(not(feature = "json5"))]
+    let file = DataFile::parse(&contents, Syntax::Json, progress);
+
+    file.map(|file| file.emojis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Build a dataset entry with just the required fields
+    @param glyph: Emoji glyph
+    @param keywords: Comma separated keywords
+    @param category: Category name
+    @return EmojiData: Entry as if loaded from a data file
+    */
+    fn emoji(glyph: &str, keywords: &str, category: &str) -> EmojiData {
+        serde_json::from_value(serde_json::json!({
+            "emoji": glyph,
+            "keywords": keywords,
+            "category": category,
+        }))
+        .unwrap()
+    }
+
+    /**
+    Parse strict JSON without reporting progress
+    @param contents: Data file contents
+    @return Result<DataFile, LoadError>: Parsed data file
+    */
+    fn parse(contents: &str) -> Result<DataFile, LoadError> {
+        DataFile::parse(contents, Syntax::Json, &mut |_, _| {})
+    }
+
+    /**
+    A bare array still loads, as version 1
+    */
+    #[test]
+    fn bare_array_is_version_one() {
+        let file =
+            parse(r#"[{"emoji": "😀", "keywords": "grin", "category": "Smileys"}]"#).unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.emojis.len(), 1);
+        assert_eq!(file.emojis[0].emoji, "😀");
+    }
+
+    /**
+    The versioned wrapper loads with its declared version
+    */
+    #[test]
+    fn versioned_file_keeps_its_version() {
+        let file = parse(
+            r#"{"version": 1, "emojis": [
+                {"emoji": "😀", "keywords": "grin", "category": "Smileys"},
+                {"emoji": "🍕", "keywords": "pizza", "category": "Food"}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(file.version, 1);
+        assert_eq!(file.emojis.len(), 2);
+        assert_eq!(file.emojis[1].emoji, "🍕");
+    }
+
+    /**
+    A newer version loads on a best-effort basis, and is incompatible if its entries don't fit
+    */
+    #[test]
+    fn newer_version_is_best_effort() {
+        let readable = parse(
+            r#"{"version": 9, "emojis": [
+                {"emoji": "😀", "keywords": "grin", "category": "Smileys", "future": true}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(readable.version, 9);
+        assert_eq!(readable.emojis.len(), 1);
+
+        let unreadable = parse(r#"{"version": 9, "emojis": [{"glyph": "😀"}]}"#);
+        assert!(matches!(unreadable, Err(LoadError::Incompatible(9))));
+    }
+
+    /**
+    A broken entry in a supported version is a parse error rather than an incompatibility
+    */
+    #[test]
+    fn broken_entry_is_a_parse_error() {
+        assert!(matches!(
+            parse(r#"{"version": 1, "emojis": [{"glyph": "😀"}]}"#),
+            Err(LoadError::Parse { .. })
+        ));
+        assert!(matches!(
+            parse(r#"[{"glyph": "😀"}]"#),
+            Err(LoadError::Parse { .. })
+        ));
+        assert!(matches!(
+            parse(r#"{"emojis": []}"#),
+            Err(LoadError::Parse { .. })
+        ));
+    }
+
+    /**
+    Present glyphs and their variants resolve to their entry, with or without selectors
+    */
+    #[test]
+    fn lookup_finds_present_glyphs() {
+        let mut wave = emoji("👋", "waving hand", "People & Body");
+        wave.variants = vec!["👋🏽".to_string()];
+        let emojis = vec![
+            emoji("😀", "grinning face", "Smileys & Emotion"),
+            emoji("❤\u{FE0F}", "red heart", "Smileys & Emotion"),
+            wave,
+        ];
+        let index = GlyphIndex::build(&emojis);
+
+        assert_eq!(index.position("😀"), Some(0));
+        assert_eq!(
+            index.lookup(&emojis, "😀").unwrap().keywords,
+            "grinning face"
+        );
+        assert_eq!(index.position("❤\u{FE0F}"), Some(1));
+        assert_eq!(index.position("❤"), Some(1));
+        assert_eq!(index.lookup(&emojis, "👋🏽").unwrap().emoji, "👋");
+    }
+
+    /**
+    Glyphs missing from the dataset resolve to nothing
+    */
+    #[test]
+    fn lookup_misses_absent_glyphs() {
+        let emojis = vec![emoji("😀", "grinning face", "Smileys & Emotion")];
+        let index = GlyphIndex::build(&emojis);
+
+        assert_eq!(index.position("🍕"), None);
+        assert!(index.lookup(&emojis, "🍕").is_none());
+        assert!(index.lookup(&emojis, "").is_none());
+        assert!(GlyphIndex::default().lookup(&emojis, "😀").is_none());
+    }
+
+    /**
+    Glyphs that only differ by FE0F are duplicates when normalized, and distinct when strict
+    */
+    #[test]
+    fn dedup_ignores_fe0f_only_when_normalized() {
+        let emojis = || {
+            vec![
+                emoji("\u{2764}\u{FE0F}", "red heart", "Smileys"),
+                emoji("😀", "grinning face", "Smileys"),
+                emoji("\u{2764}", "heart, text style", "Smileys"),
+                emoji("😀", "grinning face again", "Smileys"),
+            ]
+        };
+        let glyphs = |kept: Vec<EmojiData>| -> Vec<String> {
+            kept.into_iter().map(|entry| entry.emoji).collect()
+        };
+
+        assert_eq!(
+            glyphs(dedup(emojis(), DedupKey::Normalized)),
+            ["\u{2764}\u{FE0F}", "😀"]
+        );
+        assert_eq!(
+            glyphs(dedup(emojis(), DedupKey::Strict)),
+            ["\u{2764}\u{FE0F}", "😀", "\u{2764}"]
+        );
+        assert_eq!(
+            dedup(emojis(), DedupKey::Normalized)[0].keywords,
+            "red heart"
+        );
+    }
+
+    /**
+    Progress is reported while a large file is still being parsed, rising to its full size
+    */
+    #[test]
+    fn progress_follows_the_parse() {
+        let entries: Vec<serde_json::Value> = (0..3000)
+            .map(|i| {
+                serde_json::json!({
+                    "emoji": "😀",
+                    "keywords": format!("grinning face {}", i),
+                    "category": "Smileys",
+                })
+            })
+            .collect();
+        let contents = serde_json::to_string(&entries).unwrap();
+        assert!(contents.len() > 2 * PROGRESS_BYTES);
+
+        let mut reports = Vec::new();
+        let file = DataFile::parse(&contents, Syntax::Json, &mut |parsed, total| {
+            reports.push((parsed, total))
+        })
+        .unwrap();
+        assert_eq!(file.emojis.len(), 3000);
+        assert!(reports.len() > 2, "only {:?}", reports);
+        assert!(reports.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(reports.iter().all(|&(_, total)| total == contents.len()));
+        assert_eq!(reports.last(), Some(&(contents.len(), contents.len())));
+    }
+
+    /**
+    Filtering keeps the allowed categories in dataset order, and an empty filter keeps everything
+    */
+    #[test]
+    fn filter_categories_keeps_allowed_emojis() {
+        let emojis = vec![
+            emoji("😀", "grinning face", "Smileys"),
+            emoji("🍕", "pizza", "Food"),
+            emoji("😂", "tears of joy", "Smileys"),
+            emoji("🐶", "dog", "Animals"),
+        ];
+        let glyphs = |filter: &CategoryFilter| -> Vec<String> {
+            filter_categories(&emojis, filter)
+                .into_iter()
+                .map(|entry| entry.emoji)
+                .collect()
+        };
+
+        assert_eq!(glyphs(&CategoryFilter::default()), ["😀", "🍕", "😂", "🐶"]);
+        let allow = CategoryFilter {
+            allow: vec!["smileys".to_string()],
+            deny: Vec::new(),
+        };
+        assert_eq!(glyphs(&allow), ["😀", "😂"]);
+        let deny = CategoryFilter {
+            allow: Vec::new(),
+            deny: vec!["FOOD".to_string()],
+        };
+        assert_eq!(glyphs(&deny), ["😀", "😂", "🐶"]);
+    }
+}
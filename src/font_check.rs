@@ -0,0 +1,64 @@
+use ttf_parser::{Face, FaceParsingError};
+
+/**
+Codepoints that never need a glyph of their own
+- Zero width joiner and the text/emoji presentation selectors only change how neighbours render
+*/
+const INVISIBLE_CODEPOINTS: [char; 3] = ['\u{200D}', '\u{FE0E}', '\u{FE0F}'];
+
+/**
+Result of checking a dataset against a font
+*/
+pub struct Coverage {
+    pub total: usize,
+    pub missing: Vec<String>, // Emojis the font has no glyphs for
+}
+
+impl Coverage {
+    /**
+    Number of emojis the font can render
+    @param &self: Self reference
+    @return usize: Count of renderable emojis
+    */
+    pub fn renderable(&self) -> usize {
+        self.total - self.missing.len()
+    }
+}
+
+/**
+Check which emojis a font has glyphs for
+@param font_data: Raw bytes of the font file
+@param emojis: Emoji strings to check
+@return Result<Coverage, FaceParsingError>: Coverage summary, or an error if the font can't be parsed
+*/
+pub fn check<'a>(
+    font_data: &[u8],
+    emojis: impl IntoIterator<Item = &'a str>,
+) -> Result<Coverage, FaceParsingError> {
+    let face = Face::parse(font_data, 0)?;
+
+    let mut total = 0;
+    let mut missing = Vec::new();
+    for emoji in emojis {
+        total += 1;
+        if !has_glyphs(&face, emoji) {
+            missing.push(emoji.to_string());
+        }
+    }
+
+    Ok(Coverage { total, missing })
+}
+
+/**
+Check if a font has a glyph for every visible codepoint of an emoji
+- ZWJ sequences are treated as renderable when all of their parts are, even if the font lacks the combined ligature
+@param face: Parsed font face
+@param emoji: Emoji to check
+@return bool: True if every visible codepoint has a glyph
+*/
+fn has_glyphs(face: &Face, emoji: &str) -> bool {
+    emoji
+        .chars()
+        .filter(|c| !INVISIBLE_CODEPOINTS.contains(c))
+        .all(|c| face.glyph_index(c).is_some())
+}
@@ -0,0 +1,339 @@
+use serde::Deserialize;
+use std::borrow::Cow;
+
+/**
+Ways an emoji can be copied to the clipboard
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Representation {
+    #[default]
+    Glyph, // The emoji itself, e.g. 😀
+    Shortcode,  // Derived from the primary keyword, e.g. :grinning_face:
+    Codepoints, // e.g. U+1F600
+    HtmlEntity, // e.g. &#x1F600;
+    Escape,     // Rust/JS source escape, e.g. \u{1F600}
+}
+
+impl Representation {
+    pub const ALL: [Representation; 5] = [
+        Representation::Glyph,
+        Representation::Shortcode,
+        Representation::Codepoints,
+        Representation::HtmlEntity,
+        Representation::Escape,
+    ];
+
+    /**
+    Label shown in the context menu
+    @param &self: Self reference
+    @return &'static str: Human readable name
+    */
+    pub fn label(&self) -> &'static str {
+        match self {
+            Representation::Glyph => "Glyph",
+            Representation::Shortcode => "Shortcode",
+            Representation::Codepoints => "Codepoints",
+            Representation::HtmlEntity => "HTML",
+            Representation::Escape => "Escape",
+        }
+    }
+
+    /**
+    Format an emoji in this representation
+    @param &self: Self reference
+    @param emoji: The emoji glyph
+    @param keyword: Primary keyword of the emoji, used for shortcodes
+    @return String: Formatted text to copy
+    */
+    pub fn format(&self, emoji: &str, keyword: &str) -> String {
+        match self {
+            Representation::Glyph => emoji.to_string(),
+            Representation::Shortcode => shortcode(keyword),
+            Representation::Codepoints => codepoints(emoji),
+            Representation::HtmlEntity => html_entity(emoji),
+            Representation::Escape => escape(emoji),
+        }
+    }
+}
+
+/**
+Join several copied emojis into the text put on the clipboard
+@param items: Emojis, already formatted in their representation
+@param separator: Put between items
+@param prefix: Put before the first item
+@param suffix: Put after the last item
+@return String: e.g. "[😀 🎉]" for a space separator wrapped in brackets
+*/
+pub fn join(items: &[String], separator: &str, prefix: &str, suffix: &str) -> String {
+    format!("{}{}{}", prefix, items.join(separator), suffix)
+}
+
+/**
+Format every codepoint of an emoji as U+XXXX, joined by spaces
+@param emoji: The emoji glyph, may be a multi-codepoint sequence
+@return String: e.g. "U+2764 U+FE0F"
+*/
+pub fn codepoints(emoji: &str) -> String {
+    emoji
+        .chars()
+        .map(|c| format!("U+{:04X}", c as u32))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/**
+Format every codepoint of an emoji as a hexadecimal HTML entity
+@param emoji: The emoji glyph, may be a multi-codepoint sequence
+@return String: e.g. "&#x2764;&#xFE0F;"
+*/
+pub fn html_entity(emoji: &str) -> String {
+    emoji
+        .chars()
+        .map(|c| format!("&#x{:X};", c as u32))
+        .collect()
+}
+
+/**
+Format every codepoint of an emoji as a \u{...} escape, as understood by Rust and modern JavaScript
+@param emoji: The emoji glyph, may be a multi-codepoint sequence
+@return String: e.g. "\u{2764}\u{FE0F}"
+*/
+pub fn escape(emoji: &str) -> String {
+    emoji
+        .chars()
+        .map(|c| format!("\\u{{{:X}}}", c as u32))
+        .collect()
+}
+
+/**
+Cut text down to a maximum length for display, adding an ellipsis if anything was cut
+- Never splits a grapheme: combining marks, joiners, selectors and skin tones stay with their base
+@param text: Text to shorten
+@param max_chars: Most characters to keep before the ellipsis
+@return Cow<str>: The text itself if it fits, otherwise the shortened copy
+*/
+pub fn truncate(text: &str, max_chars: usize) -> Cow<'_, str> {
+    let Some((cut, _)) = text.char_indices().nth(max_chars) else {
+        return Cow::Borrowed(text);
+    };
+
+    // Extend the cut past anything that attaches to the character before it
+    let mut end = cut;
+    let mut joined = false;
+    for (position, c) in text[cut..].char_indices() {
+        if joined || is_grapheme_extender(c) {
+            joined = c == '\u{200D}';
+            end = cut + position + c.len_utf8();
+        } else {
+            break;
+        }
+    }
+
+    if end == text.len() {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(format!("{}…", &text[..end]))
+}
+
+/**
+Quote a field for a CSV file if it needs it
+- Fields with commas, quotes or line breaks are wrapped in quotes, with their quotes doubled
+@param field: Field value
+@return Cow<str>: The field itself, or its quoted copy
+*/
+pub fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/**
+Split text into its user-perceived characters (grapheme clusters)
+- Emoji aware: ZWJ sequences, skin tones, keycaps and tag flags are one cluster, flags pair up their regional indicators
+- Not a full UAX #29 implementation, Hangul syllables and the like are split per character
+@param text: Text to split, e.g. an emoji field
+@return Vec<String>: The clusters in order, whitespace included
+*/
+pub fn split_graphemes(text: &str) -> Vec<String> {
+    let mut graphemes: Vec<String> = Vec::new();
+    let mut joined = false; // Last character was a zero width joiner
+    let mut lone_flag_half = false; // Last cluster is a regional indicator waiting for its pair
+    for c in text.chars() {
+        let regional = ('\u{1F1E6}'..='\u{1F1FF}').contains(&c);
+        let pairs = regional && lone_flag_half;
+        match graphemes.last_mut() {
+            Some(last) if joined || pairs || is_grapheme_extender(c) => last.push(c),
+            _ => graphemes.push(c.to_string()),
+        }
+        joined = c == '\u{200D}';
+        lone_flag_half = regional && !pairs;
+    }
+    graphemes
+}
+
+/**
+Check if a character attaches to the one before it rather than starting a new grapheme
+@param c: Character to check
+@return bool: True for combining marks, joiners, variation selectors and skin tone modifiers
+*/
+fn is_grapheme_extender(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'     // Combining diacritical marks
+        | '\u{1AB0}'..='\u{1AFF}'   // Combining diacritical marks extended
+        | '\u{20D0}'..='\u{20FF}'   // Combining marks for symbols, e.g. the keycap
+        | '\u{200D}'                // Zero width joiner
+        | '\u{FE00}'..='\u{FE0F}'   // Variation selectors
+        | '\u{1F3FB}'..='\u{1F3FF}' // Skin tone modifiers
+        | '\u{E0020}'..='\u{E007F}' // Tag characters, used by subdivision flags
+    )
+}
+
+/**
+Build a shortcode from a keyword
+@param keyword: Primary keyword of the emoji
+@return String: e.g. ":grinning_face:"
+*/
+pub fn shortcode(keyword: &str) -> String {
+    let name: String = keyword
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!(":{}:", name)
+}
+
+/**
+Format a count with a comma between each group of three digits
+@param count: Number to format
+@return String: e.g. "1,234,567"
+*/
+pub fn thousands(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Separators appear exactly at the group boundaries
+    */
+    #[test]
+    fn thousands_groups_digits() {
+        assert_eq!(thousands(0), "0");
+        assert_eq!(thousands(999), "999");
+        assert_eq!(thousands(1000), "1,000");
+        assert_eq!(thousands(99_999), "99,999");
+        assert_eq!(thousands(100_000), "100,000");
+        assert_eq!(thousands(1_234_567), "1,234,567");
+    }
+
+    /**
+    Single codepoints and ZWJ sequences become one escape per codepoint
+    */
+    #[test]
+    fn escape_formats_every_codepoint() {
+        assert_eq!(escape("😀"), "\\u{1F600}");
+        assert_eq!(escape("❤\u{FE0F}"), "\\u{2764}\\u{FE0F}");
+        assert_eq!(
+            escape("👨\u{200D}👩\u{200D}👧"),
+            "\\u{1F468}\\u{200D}\\u{1F469}\\u{200D}\\u{1F467}"
+        );
+        assert_eq!(
+            Representation::Escape.format("😀", "grinning face"),
+            "\\u{1F600}"
+        );
+    }
+
+    /**
+    Multi-copy joins with any separator, including none, and wraps the whole result
+    */
+    #[test]
+    fn join_applies_separator_and_wrapper() {
+        let items = ["😀", "🎉", "🍕"].map(String::from);
+        assert_eq!(join(&items, "", "", ""), "😀🎉🍕");
+        assert_eq!(join(&items, "\n", "", ""), "😀\n🎉\n🍕");
+        assert_eq!(join(&items, " ", "[", "]"), "[😀 🎉 🍕]");
+        assert_eq!(join(&items[..1], ", ", "<", ">"), "<😀>");
+        assert_eq!(join(&[], " ", "[", "]"), "[]");
+    }
+
+    /**
+    Every codepoint of a sequence is listed, padded to at least four digits
+    */
+    #[test]
+    fn codepoints_list_every_codepoint() {
+        assert_eq!(codepoints("😀"), "U+1F600");
+        assert_eq!(codepoints("❤\u{FE0F}"), "U+2764 U+FE0F");
+        assert_eq!(codepoints("👩\u{200D}💻"), "U+1F469 U+200D U+1F4BB");
+        assert_eq!(codepoints("#\u{FE0F}\u{20E3}"), "U+0023 U+FE0F U+20E3");
+    }
+
+    /**
+    A long multi-copy preview is cut between emojis, never inside a sequence or before its tone
+    */
+    #[test]
+    fn truncate_keeps_whole_emojis() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        // Both limits fall inside an emoji, on a joiner and on a skin tone
+        for (items, separator, max_chars) in [
+            (vec![family.to_string(); 30], " ", 21),
+            (vec!["👋\u{1F3FD}".to_string(); 50], "", 23),
+        ] {
+            let contents = join(&items, separator, "", "");
+            let preview = truncate(&contents, max_chars);
+            let kept = preview.strip_suffix('…').unwrap();
+            assert!(contents.starts_with(kept));
+            assert!(
+                split_graphemes(kept)
+                    .iter()
+                    .all(|grapheme| grapheme == separator || *grapheme == items[0])
+            );
+        }
+
+        assert_eq!(truncate("😀😁", 24), "😀😁");
+    }
+
+    /**
+    Plain fields pass through, fields with separators, quotes or line breaks are quoted
+    */
+    #[test]
+    fn csv_field_quotes_when_needed() {
+        assert!(matches!(
+            csv_field("grinning face"),
+            Cow::Borrowed("grinning face")
+        ));
+        assert_eq!(csv_field("😀"), "😀");
+        assert_eq!(csv_field("one, two"), "\"one, two\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+        assert_eq!(csv_field("carriage\r"), "\"carriage\r\"");
+        assert_eq!(csv_field(""), "");
+    }
+
+    /**
+    Joined sequences stay whole, neighbouring emojis don't merge
+    */
+    #[test]
+    fn split_graphemes_keeps_sequences_whole() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(split_graphemes(family), vec![family]);
+        assert_eq!(split_graphemes("😀😁"), vec!["😀", "😁"]);
+        assert_eq!(
+            split_graphemes(&format!("{}👋\u{1F3FD}🇫🇷🇩🇪", family)),
+            vec![family, "👋\u{1F3FD}", "🇫🇷", "🇩🇪"]
+        );
+    }
+}
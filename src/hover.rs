@@ -0,0 +1,168 @@
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::widget::{Tree, tree};
+use iced::advanced::{Clipboard, Shell, Widget, renderer};
+use iced::{Element, Event, Length, Rectangle, Size, event, mouse};
+
+/**
+Wrapper widget that reports when the cursor enters or leaves its content
+- Iced 0.12's MouseArea only knows about clicks, so hover needs its own widget
+*/
+pub struct Hover<'a, Message, Theme, Renderer> {
+    content: Element<'a, Message, Theme, Renderer>,
+    on_enter: Message,
+    on_exit: Message,
+}
+
+/**
+Per-widget state kept in the widget tree between frames
+*/
+#[derive(Default)]
+struct State {
+    hovered: bool,
+}
+
+impl<'a, Message, Theme, Renderer> Hover<'a, Message, Theme, Renderer> {
+    /**
+    Wrap content with hover reporting
+    @param content: Content to wrap
+    @param on_enter: Message published when the cursor enters the content
+    @param on_exit: Message published when the cursor leaves the content
+    @return Hover: The wrapping widget
+    */
+    pub fn new(
+        content: impl Into<Element<'a, Message, Theme, Renderer>>,
+        on_enter: Message,
+        on_exit: Message,
+    ) -> Self {
+        Hover {
+            content: content.into(),
+            on_enter,
+            on_exit,
+        }
+    }
+}
+
+impl<Message, Theme, Renderer> Widget<Message, Theme, Renderer>
+    for Hover<'_, Message, Theme, Renderer>
+where
+    Message: Clone,
+    Renderer: renderer::Renderer,
+{
+    fn tag(&self) -> tree::Tag {
+        tree::Tag::of::<State>()
+    }
+
+    fn state(&self) -> tree::State {
+        tree::State::new(State::default())
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(std::slice::from_ref(&self.content));
+    }
+
+    fn size(&self) -> Size<Length> {
+        self.content.as_widget().size()
+    }
+
+    fn layout(
+        &self,
+        tree: &mut Tree,
+        renderer: &Renderer,
+        limits: &layout::Limits,
+    ) -> layout::Node {
+        self.content
+            .as_widget()
+            .layout(&mut tree.children[0], renderer, limits)
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        // Only publish on transitions, so moving within a cell doesn't flood the update loop
+        if let Event::Mouse(mouse::Event::CursorMoved { .. } | mouse::Event::CursorLeft) = event {
+            let state = tree.state.downcast_mut::<State>();
+            let hovered = cursor.is_over(layout.bounds());
+            if hovered != state.hovered {
+                state.hovered = hovered;
+                shell.publish(if hovered {
+                    self.on_enter.clone()
+                } else {
+                    self.on_exit.clone()
+                });
+            }
+        }
+
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+}
+
+impl<'a, Message, Theme, Renderer> From<Hover<'a, Message, Theme, Renderer>>
+    for Element<'a, Message, Theme, Renderer>
+where
+    Message: Clone + 'a,
+    Theme: 'a,
+    Renderer: renderer::Renderer + 'a,
+{
+    fn from(hover: Hover<'a, Message, Theme, Renderer>) -> Self {
+        Element::new(hover)
+    }
+}
@@ -0,0 +1,107 @@
+use crate::data::{self, EmojiData};
+use crate::format;
+use std::collections::HashMap;
+use std::fmt;
+
+/**
+How bad a lint finding is, only errors fail the lint
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,   // The picker would misbehave, e.g. a blank cell or a glyph shadowing another
+    Warning, // Worth a look, e.g. a category the embedded data doesn't have
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/**
+A single problem with a dataset entry
+*/
+#[derive(Debug)]
+pub struct Issue {
+    pub severity: Severity,
+    pub entry: usize, // Position in the dataset, counting from 1
+    pub glyph: String,
+    pub message: String,
+}
+
+impl Issue {
+    /**
+    Greppable report line, `file:entry: severity: glyph: message`
+    @param &self: Self reference
+    @param file: Name of the linted file
+    @return String: Report line
+    */
+    pub fn report_line(&self, file: &str) -> String {
+        format!(
+            "{}:{}: {}: {:?}: {}",
+            file, self.entry, self.severity, self.glyph, self.message
+        )
+    }
+}
+
+/**
+Check a dataset for entries that would show up wrong in the picker
+- Glyphs are compared without presentation selectors, so "❤" and "❤️" are duplicates
+@param emojis: Dataset to check
+@param known_categories: Categories of the embedded data, others are reported as unknown
+@return Vec<Issue>: Findings in dataset order
+*/
+pub fn check(emojis: &[EmojiData], known_categories: &[String]) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for (position, emoji) in emojis.iter().enumerate() {
+        let entry = position + 1;
+        let mut report = |severity: Severity, message: String| {
+            issues.push(Issue {
+                severity,
+                entry,
+                glyph: emoji.emoji.clone(),
+                message,
+            });
+        };
+
+        let glyph = data::normalize_glyph(&emoji.emoji);
+        if glyph.trim().is_empty() {
+            report(Severity::Error, "empty glyph".to_string());
+        } else if let Some(first) = first_seen.get(&glyph) {
+            report(
+                Severity::Error,
+                format!("duplicate glyph, first used by entry {}", first),
+            );
+        } else {
+            first_seen.insert(glyph, entry);
+        }
+
+        // A field holding several emojis is usually a missing comma in the source data
+        let graphemes = format::split_graphemes(emoji.emoji.trim());
+        if graphemes.len() > 1 {
+            report(
+                Severity::Warning,
+                format!(
+                    "holds {} emojis, probably meant as separate entries: {}",
+                    graphemes.len(),
+                    graphemes.join(" ")
+                ),
+            );
+        }
+
+        if emoji.keywords.trim().is_empty() {
+            report(Severity::Error, "empty keywords".to_string());
+        }
+        if !known_categories.contains(&emoji.category) {
+            report(
+                Severity::Warning,
+                format!("unknown category {:?}", emoji.category),
+            );
+        }
+    }
+    issues
+}
@@ -1,7 +1,9 @@
-use std::sync::{Once, OnceLock};
+use std::collections::VecDeque;
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, Once, OnceLock};
 use std::thread;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 /**
 Define the message structure sent over the channel
@@ -14,8 +16,474 @@ pub struct LogMessage {
 }
 
 static MIN_LEVEL: OnceLock<Level> = OnceLock::new();
-static LOG_CHANNEL_SENDER: OnceLock<mpsc::Sender<LogMessage>> = OnceLock::new();
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+// Messages waiting for the worker, the oldest are dropped when it falls behind
+static BACKLOG: Backlog = Backlog::new();
 static SPAWN_WORKER_ONCE: Once = Once::new();
+static WORKER_STARTED: AtomicBool = AtomicBool::new(false);
+// Messages sent to the worker but not yet written out
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+// Throughput counters, see stats()
+static ENQUEUED: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+static EVICTED: AtomicU64 = AtomicU64::new(0);
+static WRITTEN: AtomicU64 = AtomicU64::new(0);
+// Backlog drops not warned about yet, reported as one line instead of one per message
+static DROPPED_UNREPORTED: AtomicU64 = AtomicU64::new(0);
+// Recently written lines, kept in memory within the configured limits
+static HISTORY: Mutex<History> = Mutex::new(History::new());
+// Where lines are written, stderr unless init() was given something else
+static SINK: OnceLock<Mutex<Sink>> = OnceLock::new();
+// Last line written and how often it came in again since, see collapse_repeat()
+static LAST_LINE: Mutex<Option<Repeat>> = Mutex::new(None);
+// Layout of a written line, DEFAULT_TEMPLATE unless init() was given another
+static TEMPLATE: OnceLock<Template> = OnceLock::new();
+// Whether file names get a color of their own on a colored output, see set_module_colors()
+static MODULE_COLORS: AtomicBool = AtomicBool::new(false);
+
+/**
+256-color codes file names are colored with, picked to stay readable on dark and light terminals
+*/
+const MODULE_PALETTE: [u8; 12] = [37, 38, 43, 72, 74, 108, 110, 139, 140, 173, 179, 180];
+
+/**
+Which parts of a line get terminal colors
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Colors {
+    pub level: bool,  // The level, in its level color
+    pub module: bool, // The file name, in a color derived from it
+}
+
+/**
+Line layout used unless another template is given
+*/
+pub const DEFAULT_TEMPLATE: &str = "[{ts}] - [{level}] - [{location}]\t| {msg}";
+
+/**
+Piece of a line template
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Timestamp,
+    Level { bracketed: bool }, // Brackets right around it are colored along with it
+    File,
+    Line,
+    Column,
+    Location, // file:line:column
+    Message,
+}
+
+/**
+Parsed line template, e.g. "{ts} {level} {file}:{line} {msg}"
+- Placeholders are {ts}, {level}, {file}, {line}, {column}, {location} and {msg}, "{{" and "}}" are literal braces
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    /**
+    Parse a template once, so writing a line doesn't have to
+    @param template: Template text
+    @return Result<Template, String>: Parsed template, or which placeholder is wrong
+    */
+    pub fn parse(template: &str) -> Result<Template, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    let segment = match name.as_str() {
+                        "ts" => Segment::Timestamp,
+                        "level" => Segment::Level { bracketed: false },
+                        "file" => Segment::File,
+                        "line" => Segment::Line,
+                        "column" => Segment::Column,
+                        "location" => Segment::Location,
+                        "msg" => Segment::Message,
+                        _ => return Err(format!("unknown placeholder {{{}}}", name)),
+                    };
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(segment);
+                }
+                '}' => return Err("unmatched }, write }} for a literal brace".to_string()),
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(Template {
+            segments: bracket_levels(segments),
+        })
+    }
+
+    /**
+    Lay out a line
+    @param &self: Self reference
+    @param timestamp: Formatted timestamp
+    @param level: Level of the message
+    @param location: Where the message was logged from
+    @param message: The message itself
+    @param colors: Which parts get terminal colors
+    @return String: The line, without a newline
+    */
+    pub fn render(
+        &self,
+        timestamp: &str,
+        level: Level,
+        location: &std::panic::Location<'_>,
+        message: &str,
+        colors: Colors,
+    ) -> String {
+        let module = |line: &mut String, text: &str| {
+            if colors.module {
+                line.push_str(&module_color(location.file()));
+                line.push_str(text);
+                line.push_str("\x1b[0m");
+            } else {
+                line.push_str(text);
+            }
+        };
+        let mut line = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => line.push_str(text),
+                Segment::Timestamp => line.push_str(timestamp),
+                Segment::Level { bracketed } => {
+                    let (open, close) = if *bracketed { ("[", "]") } else { ("", "") };
+                    if colors.level {
+                        line.push_str(level.color_code());
+                    }
+                    line.push_str(open);
+                    line.push_str(level.as_str());
+                    line.push_str(close);
+                    if colors.level {
+                        line.push_str("\x1b[0m");
+                    }
+                }
+                Segment::File => module(&mut line, location.file()),
+                Segment::Line => line.push_str(&location.line().to_string()),
+                Segment::Column => line.push_str(&location.column().to_string()),
+                Segment::Location => module(&mut line, &location.to_string()),
+                Segment::Message => line.push_str(message),
+            }
+        }
+        line
+    }
+}
+
+impl Default for Template {
+    fn default() -> Self {
+        Template::parse(DEFAULT_TEMPLATE).expect("default log template is valid")
+    }
+}
+
+/**
+Pull the brackets right around each level into the level, so they get its color
+@param segments: Parsed segments
+@return Vec<Segment>: Segments with bracketed levels marked
+*/
+fn bracket_levels(mut segments: Vec<Segment>) -> Vec<Segment> {
+    for i in 0..segments.len() {
+        if segments[i] != (Segment::Level { bracketed: false }) || i == 0 {
+            continue;
+        }
+        let opens = matches!(&segments[i - 1], Segment::Literal(text) if text.ends_with('['));
+        let closes =
+            matches!(segments.get(i + 1), Some(Segment::Literal(text)) if text.starts_with(']'));
+        if !(opens && closes) {
+            continue;
+        }
+        if let Segment::Literal(text) = &mut segments[i - 1] {
+            text.pop();
+        }
+        if let Some(Segment::Literal(text)) = segments.get_mut(i + 1) {
+            text.remove(0);
+        }
+        segments[i] = Segment::Level { bracketed: true };
+    }
+    segments.retain(|segment| !matches!(segment, Segment::Literal(text) if text.is_empty()));
+    segments
+}
+
+/**
+Where log lines go
+*/
+pub enum Output {
+    Stdout,
+    Stderr,
+    Writer(Box<dyn Write + Send>), // Anything else, e.g. a file or a buffer, never colored
+}
+
+/**
+The chosen output, ready to write to
+*/
+struct Sink {
+    writer: Box<dyn Write + Send>,
+    colored: bool, // Colors only make sense on a terminal, and only if NO_COLOR isn't set
+}
+
+impl Sink {
+    /**
+    Open an output, coloring it only if it's a terminal
+    @param output: Where lines should go
+    @return Sink: Writer with its color setting
+    */
+    fn new(output: Output) -> Self {
+        match output {
+            Output::Stdout => Sink {
+                colored: std::io::stdout().is_terminal() && !no_color(),
+                writer: Box::new(std::io::stdout()),
+            },
+            Output::Stderr => Sink {
+                colored: std::io::stderr().is_terminal() && !no_color(),
+                writer: Box::new(std::io::stderr()),
+            },
+            Output::Writer(writer) => Sink {
+                writer,
+                colored: false,
+            },
+        }
+    }
+
+    /**
+    Lay out a line and write it, colored if the output takes colors
+    @param &mut self: Mutable self reference
+    @param template: Line layout
+    @param timestamp: Formatted timestamp
+    @param level: Level of the message
+    @param location: Where the message was logged from
+    @param message: The message itself
+    */
+    fn write(
+        &mut self,
+        template: &Template,
+        timestamp: &str,
+        level: Level,
+        location: &std::panic::Location<'_>,
+        message: &str,
+    ) {
+        let colors = Colors {
+            level: self.colored,
+            module: self.colored && MODULE_COLORS.load(Ordering::Relaxed),
+        };
+        let _ = writeln!(
+            self.writer,
+            "{}",
+            template.render(timestamp, level, location, message, colors)
+        );
+    }
+}
+
+/**
+A written line that may be coming in again
+*/
+struct Repeat {
+    level: Level,
+    location: &'static std::panic::Location<'static>,
+    message: String,
+    count: u64, // Identical lines swallowed since it was written
+}
+
+/**
+Where log timestamps get the current time from
+*/
+pub trait TimeSource: Send + Sync {
+    fn now(&self) -> SystemTime;
+}
+
+/**
+The real wall clock, used for every logged line
+*/
+pub struct SystemClock;
+
+impl TimeSource for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/**
+A clock stopped at one moment, so timestamps come out the same on every run
+*/
+pub struct FixedClock(pub SystemTime);
+
+impl TimeSource for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.0
+    }
+}
+
+/**
+Bounds on how much memory logging may use
+*/
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    pub channel_capacity: usize, // Messages waiting for the worker, the oldest are dropped beyond this
+    pub history_entries: usize,  // Lines kept in memory, the oldest go first
+    pub history_bytes: usize,    // Total size of the lines kept in memory
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            channel_capacity: 1024,
+            history_entries: 1000,
+            history_bytes: 256 * 1024,
+        }
+    }
+}
+
+/**
+Ring buffer of recently written lines
+*/
+struct History {
+    lines: VecDeque<(Level, String)>,
+    bytes: usize,
+    scrollback: Option<usize>, // Lines kept for the log viewer, overrides history_entries when set
+}
+
+impl History {
+    const fn new() -> Self {
+        History {
+            lines: VecDeque::new(),
+            bytes: 0,
+            scrollback: None,
+        }
+    }
+
+    /**
+    Add a line, evicting the oldest ones until the history is back within its limits
+    @param &mut self: Mutable self reference
+    @param level: Level the line was logged at
+    @param line: Line to keep
+    @param limits: Bounds to stay within
+    @return usize: Number of lines evicted
+    */
+    fn push(&mut self, level: Level, line: String, limits: &Limits) -> usize {
+        self.bytes += line.len();
+        self.lines.push_back((level, line));
+        self.trim(limits)
+    }
+
+    /**
+    Evict the oldest lines until the history is within its limits
+    @param &mut self: Mutable self reference
+    @param limits: Bounds to stay within
+    @return usize: Number of lines evicted
+    */
+    fn trim(&mut self, limits: &Limits) -> usize {
+        let entries = self.scrollback.unwrap_or(limits.history_entries);
+        let mut evicted = 0;
+        while self.lines.len() > entries || self.bytes > limits.history_bytes {
+            let Some((_, line)) = self.lines.pop_front() else {
+                break;
+            };
+            self.bytes -= line.len();
+            evicted += 1;
+        }
+        evicted
+    }
+}
+
+/**
+Bounded queue between the logging threads and the worker
+- Full means the worker fell behind, so the oldest message makes room for the newest
+*/
+struct Backlog {
+    queue: Mutex<VecDeque<LogMessage>>,
+    ready: Condvar, // Signalled when a message comes in
+}
+
+impl Backlog {
+    const fn new() -> Self {
+        Backlog {
+            queue: Mutex::new(VecDeque::new()),
+            ready: Condvar::new(),
+        }
+    }
+
+    /**
+    Queue a message without blocking, dropping the oldest one if the queue is full
+    @param &self: Self reference
+    @param log_entry: The message to queue
+    @param capacity: Most messages allowed to wait
+    @return usize: Number of messages dropped to make room
+    */
+    fn push(&self, log_entry: LogMessage, capacity: usize) -> usize {
+        let Ok(mut queue) = self.queue.lock() else {
+            return 1;
+        };
+        let mut dropped = 0;
+        while queue.len() >= capacity.max(1) {
+            queue.pop_front();
+            dropped += 1;
+        }
+        queue.push_back(log_entry);
+        self.ready.notify_one();
+        dropped
+    }
+
+    /**
+    Take the oldest message, waiting for one if the queue is empty
+    @param &self: Self reference
+    @return Option<LogMessage>: The message, None if the queue can't be used anymore
+    */
+    fn pop(&self) -> Option<LogMessage> {
+        let queue = self.queue.lock().ok()?;
+        let mut queue = self
+            .ready
+            .wait_while(queue, |queue| queue.is_empty())
+            .ok()?;
+        queue.pop_front()
+    }
+}
+
+/**
+Snapshot of the logging throughput counters
+*/
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub enqueued: u64,         // Messages handed to the worker
+    pub dropped: u64,          // Messages dropped, the oldest first, because the worker fell behind
+    pub evicted: u64,          // Lines pushed out of the history by newer ones
+    pub written: u64,          // Messages the worker has written out
+    pub retained: usize,       // Lines currently kept in memory
+    pub retained_bytes: usize, // Size of the lines kept in memory
+}
+
+/**
+Name of the worker thread, used to tell if a panic happened inside the logger itself
+*/
+const WORKER_THREAD_NAME: &str = "nicepick-logger";
+
+/**
+Least time between two warnings about dropped messages, the drops in between are added up
+*/
+const DROP_REPORT_INTERVAL: Duration = Duration::from_secs(10);
 
 /**
 Define acceptable log levels
@@ -33,6 +501,17 @@ pub enum Level {
 Log level implementation
 */
 impl Level {
+    /**
+    Every level, least severe first
+    */
+    pub const ALL: [Level; 5] = [
+        Level::Debug,
+        Level::Info,
+        Level::Okay,
+        Level::Warning,
+        Level::Fail,
+    ];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Level::Debug => "DBUG",
@@ -57,12 +536,63 @@ impl Level {
 /**
 Helper function to initialize the logging system
 @param level The minimum level to log
+@param limits Bounds on the channel backlog and the in-memory history
+@param output Where lines are written, lines logged before init go to stderr
+@param template Line layout, see Template, DEFAULT_TEMPLATE is used if it doesn't parse
 */
-pub fn init(level: Level) {
-    // Set the minimum level safely
+pub fn init(level: Level, limits: Limits, output: Output, template: &str) {
+    // Set the minimum level, limits and output safely
     let _ = MIN_LEVEL.set(level);
+    let _ = LIMITS.set(limits);
+    if SINK.set(Mutex::new(Sink::new(output))).is_err() {
+        eprintln!("Log output already chosen, ignoring the new one");
+    }
+    let parsed = Template::parse(template);
+    if TEMPLATE.set(parsed.clone().unwrap_or_default()).is_err() {
+        eprintln!("Log template already chosen, ignoring the new one");
+    }
     // Ensure the worker thread is started (if not already)
     ensure_worker_started();
+
+    if let Err(e) = parsed {
+        crate::warn!(
+            "Log template {:?} is invalid ({}), using {:?}",
+            template,
+            e,
+            DEFAULT_TEMPLATE
+        );
+    }
+}
+
+/**
+Color each file name in the log, so lines from different modules stand apart
+- Only on outputs that get colors at all, so never in files or with NO_COLOR set
+@param enabled: Whether file names are colored
+*/
+pub fn set_module_colors(enabled: bool) {
+    MODULE_COLORS.store(enabled, Ordering::Relaxed);
+}
+
+/**
+Terminal color for a source file, the same file always gets the same color
+- Hashed with FNV-1a rather than the std hasher, whose output may change between Rust versions
+@param file: Source file path, e.g. "src/search.rs"
+@return String: 256-color escape code
+*/
+pub fn module_color(file: &str) -> String {
+    let hash = file.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    let color = MODULE_PALETTE[(hash % MODULE_PALETTE.len() as u64) as usize];
+    format!("\x1b[38;5;{}m", color)
+}
+
+/**
+Check the NO_COLOR convention, any non-empty value turns colors off
+@return bool: True if colors are unwanted
+*/
+fn no_color() -> bool {
+    std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
 }
 
 /**
@@ -77,13 +607,12 @@ pub fn log_enabled(level: Level) -> bool {
 
 /**
 Helper function to get and format timestamps
+@param source: Clock to read the time from
 @return String containing the formatted timestamp
 */
-pub fn format_timestamp() -> String {
+pub fn format_timestamp(source: &dyn TimeSource) -> String {
     // Get the current time
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default();
+    let now = source.now().duration_since(UNIX_EPOCH).unwrap_or_default();
 
     // Convert to seconds and calculate date/time components
     let total_secs = now.as_secs();
@@ -151,50 +680,330 @@ fn is_leap_year(year: u64) -> bool {
 }
 
 /**
-Initialize the channel and spawn the worker thread
+Spawn the worker thread that drains the backlog
 */
 fn ensure_worker_started() {
     SPAWN_WORKER_ONCE.call_once(|| {
-        // Create bounded channel
-        let (tx, mut rx) = mpsc::channel::<LogMessage>(1024);
-        // Store the sender end
-        if LOG_CHANNEL_SENDER.set(tx).is_err() {
-            // Handle error
-            eprintln!("Logger worker already initialized.");
-            return;
-        }
+        // Logging before init gets the default limits
+        let limits = *LIMITS.get_or_init(Limits::default);
 
         // Spawn a background thread to handle actual logging
-        thread::spawn(move || {
-            // This thread owns the receiver
-            while let Some(log_entry) = rx.blocking_recv() {
-                let timestamp = format_timestamp();
-                let color_code = log_entry.level.color_code();
-                let reset_code = "\x1b[0m";
-
-                eprintln!(
-                    "[{}] - {}[{}]{} - [{}]\t| {}",
-                    timestamp,
-                    color_code,
-                    log_entry.level.as_str(),
-                    reset_code,
-                    log_entry.location,
-                    log_entry.message
-                );
-            }
-        });
+        let spawned = thread::Builder::new()
+            .name(WORKER_THREAD_NAME.to_string())
+            .spawn(move || {
+                let mut last_report: Option<Instant> = None;
+                while let Some(log_entry) = BACKLOG.pop() {
+                    // Own up to anything dropped since the last report, at most once per interval
+                    if last_report.is_none_or(|at| at.elapsed() >= DROP_REPORT_INTERVAL)
+                        && report_drops(&limits)
+                    {
+                        last_report = Some(Instant::now());
+                    }
+
+                    let repeated = match LAST_LINE.lock() {
+                        Ok(mut last) => collapse_repeat(
+                            &mut last,
+                            &log_entry,
+                            &mut |level, location, message| {
+                                write_line(level, location, message, &limits)
+                            },
+                        ),
+                        Err(_) => false,
+                    };
+                    if !repeated {
+                        write_line(
+                            log_entry.level,
+                            log_entry.location,
+                            &log_entry.message,
+                            &limits,
+                        );
+                    }
+                    WRITTEN.fetch_add(1, Ordering::Relaxed);
+                    PENDING.fetch_sub(1, Ordering::SeqCst);
+                }
+            });
+        match spawned {
+            Ok(_) => WORKER_STARTED.store(true, Ordering::SeqCst),
+            Err(e) => eprintln!("Failed to spawn logger worker: {}", e),
+        }
     });
 }
 
 /**
-Helper function to get the sender, initialize worker if needed
-@return: Sender
+Write one warning covering every message dropped from the backlog since the last one
+- History evictions aren't included, those lines were written out before they left the history
+@param limits: Bounds for the history
+@return bool: True if there was anything to report
+*/
+fn report_drops(limits: &Limits) -> bool {
+    let missed = DROPPED_UNREPORTED.swap(0, Ordering::Relaxed);
+    if missed == 0 {
+        return false;
+    }
+    let summary = if missed == 1 {
+        "1 log message dropped, oldest first, to stay within the logging limits".to_string()
+    } else {
+        format!(
+            "{} log messages dropped, oldest first, to stay within the logging limits",
+            missed
+        )
+    };
+    write_line(
+        Level::Warning,
+        std::panic::Location::caller(),
+        &summary,
+        limits,
+    );
+    true
+}
+
+/**
+Swallow a message identical to the last one, like syslog does
+- Identical means the same level, location and text
+- The count is written out as "(last message repeated N times)" once something else comes in
+@param last: The last line written, updated to this message unless it's a repeat
+@param log_entry: Message about to be written
+@param write: Writes out the repeat count of the previous line
+@return bool: True if the message was a repeat and shouldn't be written
+*/
+fn collapse_repeat(
+    last: &mut Option<Repeat>,
+    log_entry: &LogMessage,
+    write: &mut dyn FnMut(Level, &'static std::panic::Location<'static>, &str),
+) -> bool {
+    if let Some(repeat) = last.as_mut().filter(|repeat| {
+        repeat.level == log_entry.level
+            && repeat.location == log_entry.location
+            && repeat.message == log_entry.message
+    }) {
+        repeat.count += 1;
+        return true;
+    }
+
+    if let Some(repeat) = last.as_mut() {
+        report_repeats(repeat, write);
+    }
+    *last = Some(Repeat {
+        level: log_entry.level,
+        location: log_entry.location,
+        message: log_entry.message.clone(),
+        count: 0,
+    });
+    false
+}
+
+/**
+Write how often the last line came in again, if it did
+@param repeat: The last line written
+@param write: Writes out the count
+*/
+fn report_repeats(
+    repeat: &mut Repeat,
+    write: &mut dyn FnMut(Level, &'static std::panic::Location<'static>, &str),
+) {
+    if repeat.count == 0 {
+        return;
+    }
+    let summary = if repeat.count == 1 {
+        "(last message repeated 1 time)".to_string()
+    } else {
+        format!("(last message repeated {} times)", repeat.count)
+    };
+    write(repeat.level, repeat.location, &summary);
+    repeat.count = 0;
+}
+
+/**
+Write a log line to the output and keep a plain copy in the history
+@param level: Level of the message
+@param location: Where the message was logged from
+@param message: The message itself
+@param limits: Bounds for the history
+*/
+fn write_line(level: Level, location: &std::panic::Location<'_>, message: &str, limits: &Limits) {
+    let timestamp = format_timestamp(&SystemClock);
+
+    let template = TEMPLATE.get_or_init(Template::default);
+
+    let sink = SINK.get_or_init(|| Mutex::new(Sink::new(Output::Stderr)));
+    if let Ok(mut sink) = sink.lock() {
+        sink.write(template, &timestamp, level, location, message);
+    }
+
+    let line = template.render(&timestamp, level, location, message, Colors::default());
+    if let Ok(mut history) = HISTORY.lock() {
+        let evicted = history.push(level, line, limits) as u64;
+        // A full history turns over with every line, that's only counted for stats()
+        if evicted > 0 {
+            EVICTED.fetch_add(evicted, Ordering::Relaxed);
+        }
+    }
+}
+
+/**
+Hand a message to the worker without blocking
+@param log_entry: The message to log
 */
-pub fn get_sender() -> Option<&'static mpsc::Sender<LogMessage>> {
-    // Ensure worker is started on first attempt to get sender
+pub fn submit(log_entry: LogMessage) {
+    // Ensure worker is started on the first message
     ensure_worker_started();
-    // Retrieve sender
-    LOG_CHANNEL_SENDER.get()
+    if !WORKER_STARTED.load(Ordering::SeqCst) {
+        eprintln!("Logging system failed to initialize."); // Handle initialization failure
+        return;
+    }
+
+    // Count it before queueing so a flush can never miss it
+    PENDING.fetch_add(1, Ordering::SeqCst);
+    let capacity = LIMITS.get_or_init(Limits::default).channel_capacity;
+    let dropped = enqueue(&BACKLOG, log_entry, capacity);
+    // Dropped messages will never be written, so a flush shouldn't wait for them
+    PENDING.fetch_sub(dropped, Ordering::SeqCst);
+}
+
+/**
+Queue a message without blocking and count what had to make room for it
+@param backlog: Queue to the worker
+@param log_entry: The message to log
+@param capacity: Most messages allowed to wait
+@return usize: Number of older messages dropped
+*/
+fn enqueue(backlog: &Backlog, log_entry: LogMessage, capacity: usize) -> usize {
+    ENQUEUED.fetch_add(1, Ordering::Relaxed);
+    let dropped = backlog.push(log_entry, capacity);
+    if dropped > 0 {
+        DROPPED.fetch_add(dropped as u64, Ordering::Relaxed);
+        // The worker reports these in one line, a warning per drop would only add to the flood
+        DROPPED_UNREPORTED.fetch_add(dropped as u64, Ordering::Relaxed);
+    }
+    dropped
+}
+
+/**
+Set how many lines the history keeps for the log viewer, dropping the oldest ones beyond that
+- The byte limit still applies on top
+@param lines: Lines to keep
+*/
+pub fn set_scrollback(lines: usize) {
+    let limits = LIMITS.get_or_init(Limits::default);
+    if let Ok(mut history) = HISTORY.lock() {
+        history.scrollback = Some(lines);
+        // Asked for, so not worth a warning
+        history.trim(limits);
+    }
+}
+
+/**
+Search the history, oldest line first
+- Only the matching lines are copied out, the history itself stays locked for the duration
+@param filter: Text a line has to contain, empty for every line
+@param min_level: Lowest level shown
+@return (Vec<String>, usize): Matching lines and how many lines the history holds
+*/
+pub fn history(filter: &str, min_level: Level) -> (Vec<String>, usize) {
+    let Ok(history) = HISTORY.lock() else {
+        return (Vec::new(), 0);
+    };
+    let lines = history
+        .lines
+        .iter()
+        .filter(|(level, line)| *level >= min_level && line.contains(filter))
+        .map(|(_, line)| line.clone())
+        .collect();
+    (lines, history.lines.len())
+}
+
+/**
+Forget every line in the history, the output is untouched
+*/
+pub fn clear_history() {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.lines.clear();
+        history.bytes = 0;
+    }
+}
+
+/**
+Read the logging throughput counters
+@return Stats: Counts of enqueued, dropped, evicted and written messages since startup, and the history size
+*/
+pub fn stats() -> Stats {
+    let (retained, retained_bytes) = HISTORY
+        .lock()
+        .map(|history| (history.lines.len(), history.bytes))
+        .unwrap_or_default();
+    Stats {
+        enqueued: ENQUEUED.load(Ordering::Relaxed),
+        dropped: DROPPED.load(Ordering::Relaxed),
+        evicted: EVICTED.load(Ordering::Relaxed),
+        written: WRITTEN.load(Ordering::Relaxed),
+        retained,
+        retained_bytes,
+    }
+}
+
+/**
+Wait for the worker to write out everything sent so far
+- Gives up after the timeout, so a dead worker can't hang the caller
+- Also writes out how often the last line was repeated, and any drops not reported yet
+@param timeout: Maximum time to wait
+@return bool: True if everything was written
+*/
+pub fn flush(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while PENDING.load(Ordering::SeqCst) > 0 {
+        if Instant::now() >= deadline {
+            return false;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+
+    // Nothing else is coming to push out a pending repeat count or drop report, so write them now
+    let limits = LIMITS.get().copied().unwrap_or_default();
+    if let Ok(mut last) = LAST_LINE.lock() {
+        if let Some(repeat) = last.as_mut() {
+            report_repeats(repeat, &mut |level, location, message| {
+                write_line(level, location, message, &limits)
+            });
+        }
+    }
+    report_drops(&limits);
+    if let Some(Ok(mut sink)) = SINK.get().map(Mutex::lock) {
+        let _ = sink.writer.flush();
+    }
+    true
+}
+
+/**
+Install a panic hook that records panics in the log before the default handler runs
+*/
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // A panic in the worker means nobody is left to write to, so skip straight to stderr
+        let in_worker = thread::current().name() == Some(WORKER_THREAD_NAME);
+        if !in_worker && log_enabled(Level::Fail) {
+            let payload = if let Some(message) = info.payload().downcast_ref::<&str>() {
+                message.to_string()
+            } else if let Some(message) = info.payload().downcast_ref::<String>() {
+                message.clone()
+            } else {
+                String::from("<non-string panic payload>")
+            };
+            let panic_location = info
+                .location()
+                .map(|l| l.to_string())
+                .unwrap_or_else(|| String::from("<unknown location>"));
+
+            submit(LogMessage {
+                level: Level::Fail,
+                message: format!("Panicked at {}: {}", panic_location, payload),
+                location: std::panic::Location::caller(),
+            });
+            flush(Duration::from_millis(500));
+        }
+
+        default_hook(info);
+    }));
 }
 
 /**
@@ -206,26 +1015,17 @@ macro_rules! log {
     ($level:expr, $($arg:tt)+) => {{
         // Check level first to avoid unnecessary work
         if $crate::logging::log_enabled($level) {
-            // Get the sender, potentially initializing the worker thread
-            if let Some(sender) = $crate::logging::get_sender() {
-                let location = std::panic::Location::caller();
-                let message = format!($($arg)+);
-                // Construct the LogMessage - fields are now accessible
-                let log_entry = $crate::logging::LogMessage {
-                    level: $level,
-                    message,
-                    location,
-                };
-
-                // Use try_send for non-blocking behavior.
-                match sender.try_send(log_entry) {
-                    Ok(_) => {} // Message sent successfully
-                    Err(_e) => { // Handle error with bad blocking log message
-                        eprintln!("Warning: Log message dropped (channel full or closed)");
-                    }
-                }
-            }
-            else { eprintln!("Logging system failed to initialize."); } // Handle initialization failure
+            let location = std::panic::Location::caller();
+            let message = format!($($arg)+);
+            // Construct the LogMessage - fields are now accessible
+            let log_entry = $crate::logging::LogMessage {
+                level: $level,
+                message,
+                location,
+            };
+
+            // Send it off, potentially initializing the worker thread
+            $crate::logging::submit(log_entry);
         }
     }};
 }
@@ -255,3 +1055,286 @@ macro_rules! warn {
 macro_rules! fail {
     ($($arg:tt)+) => { $crate::log!($crate::logging::Level::Fail, $($arg)+) };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Message as the macros would build it
+    @param message: Text of the message
+    @return LogMessage: Info message logged from here
+    */
+    fn entry(message: &str) -> LogMessage {
+        LogMessage {
+            level: Level::Info,
+            message: message.to_string(),
+            location: std::panic::Location::caller(),
+        }
+    }
+
+    /**
+    Flooding a full backlog counts every message that had to make room as dropped
+    - The counters are shared with the rest of the process, so only their growth is checked
+    */
+    #[test]
+    fn flooding_the_backlog_counts_drops() {
+        let backlog = Backlog::new();
+        let before = stats();
+
+        let dropped: usize = (0..100)
+            .map(|i| enqueue(&backlog, entry(&format!("flood {}", i)), 4))
+            .sum();
+
+        let after = stats();
+        assert_eq!(dropped, 96);
+        assert!(after.enqueued - before.enqueued >= 100);
+        assert!(after.dropped - before.dropped >= 96);
+    }
+
+    /**
+    A flooded backlog keeps the newest messages, in order
+    */
+    #[test]
+    fn full_backlog_drops_the_oldest() {
+        let backlog = Backlog::new();
+        for i in 0..10 {
+            backlog.push(entry(&format!("message {}", i)), 4);
+        }
+
+        let kept: Vec<String> = (0..4).map(|_| backlog.pop().unwrap().message).collect();
+        assert_eq!(kept, ["message 6", "message 7", "message 8", "message 9"]);
+    }
+
+    /**
+    A flooded history keeps the newest lines within both its line and byte limits
+    */
+    #[test]
+    fn full_history_evicts_the_oldest() {
+        let limits = Limits {
+            channel_capacity: 4,
+            history_entries: 3,
+            history_bytes: 1024,
+        };
+        let mut history = History::new();
+        let evicted: usize = (0..10)
+            .map(|i| history.push(Level::Info, format!("line {}", i), &limits))
+            .sum();
+
+        assert_eq!(evicted, 7);
+        let lines: Vec<&str> = history
+            .lines
+            .iter()
+            .map(|(_, line)| line.as_str())
+            .collect();
+        assert_eq!(lines, ["line 7", "line 8", "line 9"]);
+
+        let tight = Limits {
+            history_bytes: 12,
+            ..limits
+        };
+        assert_eq!(history.push(Level::Info, "line 10".to_string(), &tight), 3);
+        assert_eq!(history.lines.len(), 1);
+        assert_eq!(history.bytes, "line 10".len());
+    }
+
+    /**
+    Format the moment a number of seconds after the Unix epoch
+    @param secs: Seconds since the epoch
+    @return String: Timestamp as written in the log
+    */
+    fn timestamp_at(secs: u64) -> String {
+        format_timestamp(&FixedClock(UNIX_EPOCH + Duration::from_secs(secs)))
+    }
+
+    /**
+    Leap days exist in leap years only, century years needing to be divisible by 400
+    */
+    #[test]
+    fn timestamps_handle_leap_years() {
+        assert_eq!(timestamp_at(0), "1970-01-01 00:00:00");
+        assert_eq!(timestamp_at(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(timestamp_at(1_709_251_199), "2024-02-29 23:59:59");
+        assert_eq!(timestamp_at(1_677_628_799), "2023-02-28 23:59:59");
+        assert_eq!(timestamp_at(4_107_542_399), "2100-02-28 23:59:59");
+    }
+
+    /**
+    The last second of a day, month or year rolls over into the next one
+    */
+    #[test]
+    fn timestamps_roll_over() {
+        assert_eq!(timestamp_at(86_399), "1970-01-01 23:59:59");
+        assert_eq!(timestamp_at(86_400), "1970-01-02 00:00:00");
+        assert_eq!(timestamp_at(1_709_251_200), "2024-03-01 00:00:00");
+        assert_eq!(timestamp_at(1_677_628_800), "2023-03-01 00:00:00");
+        assert_eq!(timestamp_at(4_107_542_400), "2100-03-01 00:00:00");
+        assert_eq!(timestamp_at(1_735_689_599), "2024-12-31 23:59:59");
+        assert_eq!(timestamp_at(1_735_689_600), "2025-01-01 00:00:00");
+    }
+
+    /**
+    Identical messages in a row collapse into one line and a repeat count
+    */
+    #[test]
+    fn repeated_messages_collapse() {
+        let mut last = None;
+        let mut written: Vec<String> = Vec::new();
+        let mut log = |message: &LogMessage| {
+            let mut write = |_: Level, _: &'static std::panic::Location<'static>, line: &str| {
+                written.push(line.to_string())
+            };
+            if !collapse_repeat(&mut last, message, &mut write) {
+                write(message.level, message.location, &message.message);
+            }
+        };
+
+        let font_error = entry("Font failed to load");
+        for _ in 0..5 {
+            log(&font_error);
+        }
+        log(&entry("Something else"));
+        log(&entry("Something else"));
+        log(&font_error);
+
+        assert_eq!(
+            written,
+            [
+                "Font failed to load",
+                "(last message repeated 4 times)",
+                "Something else",
+                "(last message repeated 1 time)",
+                "Font failed to load",
+            ]
+        );
+        assert_eq!(last.as_ref().map(|repeat| repeat.count), Some(0));
+    }
+
+    /**
+    Writer into a buffer the test can still read after handing it to a sink
+    */
+    #[derive(Clone, Default)]
+    struct SharedBuffer(std::sync::Arc<Mutex<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, bytes: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(bytes);
+            Ok(bytes.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /**
+    A custom writer is never colored and gets one line per message
+    */
+    #[test]
+    fn custom_writer_gets_plain_lines() {
+        let buffer = SharedBuffer::default();
+        let mut sink = Sink::new(Output::Writer(Box::new(buffer.clone())));
+        assert!(!sink.colored);
+
+        let template = Template::parse("{level} {msg}").unwrap();
+        let location = std::panic::Location::caller();
+        sink.write(
+            &template,
+            "2024-01-01 00:00:00",
+            Level::Warning,
+            location,
+            "first",
+        );
+        sink.write(
+            &template,
+            "2024-01-01 00:00:01",
+            Level::Info,
+            location,
+            "second",
+        );
+
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(written, "WARN first\nINFO second\n");
+    }
+
+    /**
+    A custom template reorders and drops fields, with literal braces kept
+    */
+    #[test]
+    fn custom_template_renders_a_message() {
+        let location = std::panic::Location::caller();
+        let template = Template::parse("{{{level}}} {file}:{line} {msg} @ {ts}").unwrap();
+        let line = template.render(
+            "2024-02-29 12:00:00",
+            Level::Fail,
+            location,
+            "font missing",
+            Colors::default(),
+        );
+        assert_eq!(
+            line,
+            format!(
+                "{{FAIL}} {}:{} font missing @ 2024-02-29 12:00:00",
+                location.file(),
+                location.line()
+            )
+        );
+    }
+
+    /**
+    The default template keeps the original layout, coloring the level with its brackets
+    */
+    #[test]
+    fn default_template_matches_the_original_format() {
+        let location = std::panic::Location::caller();
+        let template = Template::default();
+        let plain = template.render("ts", Level::Info, location, "hi", Colors::default());
+        assert_eq!(plain, format!("[ts] - [INFO] - [{}]\t| hi", location));
+
+        let colors = Colors {
+            level: true,
+            module: false,
+        };
+        let colored = template.render("ts", Level::Info, location, "hi", colors);
+        assert!(colored.contains(&format!("{}[INFO]\x1b[0m", Level::Info.color_code())));
+    }
+
+    /**
+    Unknown placeholders and stray braces are rejected so init can fall back to the default
+    */
+    #[test]
+    fn invalid_templates_are_rejected() {
+        assert!(Template::parse("{ts} {lvl} {msg}").is_err());
+        assert!(Template::parse("{msg").is_err());
+        assert!(Template::parse("msg}").is_err());
+        assert!(Template::parse("").is_ok());
+    }
+
+    /**
+    A file always gets the same color from the palette, and only when module colors are on
+    */
+    #[test]
+    fn module_color_is_stable_per_file() {
+        assert_eq!(module_color("src/search.rs"), module_color("src/search.rs"));
+        for file in ["src/main.rs", "src/search.rs", "src/logging.rs", ""] {
+            let color = module_color(file);
+            let code: u8 = color
+                .strip_prefix("\x1b[38;5;")
+                .and_then(|rest| rest.strip_suffix('m'))
+                .and_then(|code| code.parse().ok())
+                .unwrap();
+            assert!(MODULE_PALETTE.contains(&code));
+        }
+
+        let location = std::panic::Location::caller();
+        let template = Template::parse("{file} {msg}").unwrap();
+        let colors = Colors {
+            level: false,
+            module: true,
+        };
+        let colored = template.render("ts", Level::Info, location, "hi", colors);
+        assert!(colored.starts_with(&module_color(location.file())));
+        let plain = template.render("ts", Level::Info, location, "hi", Colors::default());
+        assert!(!plain.contains('\x1b'));
+    }
+}
@@ -1,44 +1,272 @@
+#[cfg(feature = "animated-preview")]
+mod animation;
+mod appearance;
+mod config;
+mod data;
+mod font_check;
+mod format;
+mod hover;
+mod lint;
 mod logging;
+mod monitor;
+mod paths;
+mod presentation;
+mod random;
+mod remote;
+mod render_test;
+mod search;
+mod shortcuts;
+mod skin_tone;
+mod storage;
+mod system_clipboard;
+mod toast;
+use appearance::Appearance;
+use config::{
+    Anchor, CategoryFilter, CellShape, ClickMode, ClipboardTarget, Config, DedupKey, EmojiAlign,
+    Feedback, HexColor, Layout, PostCopy, ScrollMode, SearchPosition, SearchScope, SectionSizes,
+    SortMode, ThemeMode, View,
+};
+use data::{Badge, EmojiData, GlyphIndex};
+use format::Representation;
+use hover::Hover;
 use logging::Level;
+use presentation::Presentation;
+use random::Rng;
+use shortcuts::{Action, Direction, Keymap};
+use skin_tone::SkinTone;
+use storage::{ClearTarget, UserData};
+use toast::{MAX_TOASTS, Toast, ToastLevel};
 
-use iced::widget::{Column, Row, Scrollable, scrollable};
-use iced::widget::{Container, container, row, text}; // Import Container
+use iced::futures::SinkExt;
+use iced::keyboard::{self, Key};
+use iced::widget::{Column, Row, Scrollable, progress_bar, scrollable};
+use iced::widget::{Container, Text, button, container, mouse_area, row, text, text_input}; // Import Container
 use iced::{
-    Alignment, Application, Color, Command, Element, Font, Length, Renderer, Settings, Size, Theme,
-    alignment, executor, font, window,
+    Alignment, Application, Border, Color, Command, Element, Event, Font, Length, Padding, Point,
+    Renderer, Settings, Size, Subscription, Theme, alignment, clipboard, event, executor, font,
+    mouse, subscription, theme, window,
 };
-use serde::Deserialize;
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, UNIX_EPOCH};
 
 /**
-Emoji data structure
+Application state struct
 */
-#[derive(Debug, Clone, Deserialize)]
-struct EmojiData {
-    emoji: String,
-    keywords: String,
-    category: String,
+struct NicePickApp {
+    dataset: Vec<EmojiData>,    // Everything loaded, before the category filter
+    emojis: Vec<EmojiData>,     // Field to store emoji data
+    load_error: Option<String>, // Set when the data file is from an incompatible version
+    data_path: Option<PathBuf>, // External data file, loaded in the background
+    data_url: Option<String>,   // Where the data file is fetched from if it isn't cached yet
+    dedup_key: DedupKey,        // How duplicate entries in the data file are recognized
+    loading: Option<(usize, usize)>, // Bytes parsed and file size while the data file loads
+    glyph_index: GlyphIndex,    // Position of each glyph in emojis, rebuilt when the data changes
+    data_generation: u64,       // Bumped when the emojis or user data change
+    visible_cache: RefCell<Option<(VisibleKey, Vec<usize>)>>, // Last visible indices and their key
+    emoji_font_loaded: bool,    // Flag to track if the emoji font is loaded
+    placeholder: Option<String>, // Shown in cells until the font loads, None for a spinner
+    started: Instant,           // When the app started, drives the spinner
+    idle_timeout: Option<Duration>, // Close the window after this long without input
+    post_copy: PostCopy,        // What the window does after a copy
+    section_sizes: SectionSizes, // Emoji size of each section above the grid
+    section_gap: u16,           // Space between the sections above the grid and the grid
+    close_at: Option<Instant>,  // When the window closes after a copy, see PostCopy
+    unconfirmed: Option<usize>, // Writes of the last copy not confirmed yet, None once one failed
+    last_activity: Instant,     // Last keyboard or mouse input
+    rng: Rng,                   // Per-run generator used by the random pick action
+    sort_mode: SortMode,        // Order of the grid when no filter is applied
+    user_data: UserData,        // Recents, favorites and usage, persisted between runs
+    fresh_base: Option<UserData>, // On-disk data a fresh session left out, saved under it
+    unsaved_since: Option<Instant>, // When user data first changed since the last save
+    scroll_mode: ScrollMode,    // Scroll through everything or flip through pages
+    layout: Layout,             // Grid of glyphs or list with names
+    rows_per_page: usize,       // Rows shown per page in paged mode
+    columns: usize,             // Grid columns for the current window width
+    window_width: f32,          // Current window width, for the column count of each section
+    min_columns: usize,         // Configured column bounds, columns stays within them
+    max_columns: usize,
+    page: usize,                 // Current page in paged mode
+    context_menu: Option<usize>, // Emoji whose copy-as menu is open
+    categories: Vec<String>,
+    category_filter: CategoryFilter, // Configured category allow and deny lists
+    show_hidden_categories: bool,    // Override showing the filtered categories
+    newest_version: Option<String>,  // Emoji version whose emojis get the new badge
+    new_only: bool,                  // Only show the emojis with the new badge
+    // Distinct categories in dataset order
+    view: View,                             // Which set of emojis the grid shows
+    scroll_offsets: HashMap<View, f32>,     // Last scroll position per view
+    query: String,                          // Current search query
+    retain_query: bool,                     // Keep the query after copying the top search result
+    escape_clears_query: bool,              // Escape clears the query before closing the window
+    log_copy_queries: bool,                 // Log the query each copy came from
+    search_follows_view: bool,              // Search only the open view instead of everything
+    min_query_len: usize,                   // Characters typed before the search filters anything
+    toasts: Vec<Toast>,                     // Transient messages, oldest first
+    show_help: bool,                        // Keyboard shortcut overlay is open
+    confirm_clears: bool,                   // Ask before clearing parts of the user data
+    pending: Option<Pending>, // Action waiting for a yes or no in the confirmation overlay
+    show_log: bool,           // Log viewer is open
+    log_filter: String,       // Text the log viewer's lines have to contain
+    log_level: Level,         // Lowest level the log viewer shows
+    settings: Option<ThemeMode>, // Theme previewed in the open settings overlay, None while closed
+    font_path: Option<PathBuf>, // Emoji font to load instead of the bundled one
+    font_requested: bool,     // Font load has been kicked off
+    font_retry_count: u32,    // Failed font loads so far
+    font_retry_at: Option<Instant>, // When the next font load attempt is due
+    font_gave_up: bool,       // Retries ran out, glyphs use the system fonts
+    synonyms: HashMap<String, Vec<String>>, // Lowercased search synonyms from the config
+    search_scope: SearchScope, // Fields the search query is matched against
+    search_position: SearchPosition, // Search box above or below the results
+    case_sensitive: bool,     // Match the query's case exactly, accents are ignored either way
+    whole_word_below: usize,  // Queries shorter than this prefer whole-word matches
+    feedback: Feedback,       // How copies are confirmed
+    flash: Option<(usize, Instant)>, // Copied cell being flashed and when the flash started
+    clipboard_target: ClipboardTarget, // Clipboard, primary selection or both
+    clipboard_stash: ClipboardStash, // Clipboard contents before the last copy, for undo
+    copy_log: Vec<(Instant, String)>, // Everything copied this session, oldest first
+    last_copy: Option<(String, Instant)>, // Last copied glyph and when, to pair it with the next copy
+    copy_as: Representation,              // Default form for copies, the copy-as menu overrides it
+    selection: Vec<usize>, // Emojis picked with ctrl+click, in the order they were picked
+    modifiers: keyboard::Modifiers, // Modifier keys currently held, for ctrl+click and shift+click
+    multi_copy: (String, String, String), // Separator, prefix and suffix for copying the selection
+    copy_suffix: String,   // Appended once to everything copied, after multi_copy's suffix
+    click_mode: ClickMode, // Single click copies, or previews with double-click to copy
+    last_click: Option<(usize, Instant)>, // Last emoji clicked and when, for double-click detection
+    variant_picker: Option<usize>, // Emoji whose variant picker is open
+    result_limit: usize,   // Maximum number of emojis rendered in scroll mode
+    merge_frequent: bool,  // One frequent tab instead of recents and favorites
+    tag_filter: Option<String>, // Only show emojis with this tag, on top of the view and search
+    tag_draft: String,     // Tag being typed in the copy-as menu
+    focus: Option<usize>,  // Emoji with keyboard focus, moved with the arrow keys
+    viewport: Option<(f32, f32)>, // (viewport height, content height), for paging and revealing focus
+    hovered: Option<usize>,       // Emoji currently under the cursor
+    theme_mode: ThemeMode,        // Configured light, dark or system theme
+    system_appearance: Option<Appearance>, // Desktop preference in system mode, None until read
+    hover_color: Option<HexColor>, // Configured hover highlight, None to follow the theme
+    emoji_align: EmojiAlign,      // Where emojis sit vertically in their cells
+    cell_shape: CellShape,        // Square or circular cell highlight
+    animations: bool,             // Slide the results in when the filter changes
+    filter_changed: Option<Instant>, // When the results last changed, while they're sliding in
+    emoji_offset: f32,            // Extra downward nudge of emojis in their cells
+    corner_radius: f32,           // Rounding of the window corners, 0 for square
+    compare: Vec<String>,         // Glyphs pinned side by side in the compare panel
+    detail: Option<usize>,        // Emoji shown in the detail panel, the last one hovered
+    show_codepoints: bool,        // Whether the detail panel lists codepoints and Unicode version
+    show_source: bool,            // Whether the detail panel names the data an emoji came from
+    nav_repeat: Option<Duration>, // Least time between held focus moves, None for the OS key repeat
+    last_nav: Option<(Direction, Instant)>, // Last focus move while throttled, cleared on key release
+    #[cfg(feature = "animated-preview")]
+    animation_dir: Option<PathBuf>, // Directory of animated preview assets
+    #[cfg(feature = "animated-preview")]
+    animation: Option<animation::Animation>, // Animated preview of the hovered emoji
 }
 
 /**
-Application state struct
+Action held back until the confirmation overlay is answered
 */
-struct NicePickApp {
-    emojis: Vec<EmojiData>,  // Field to store emoji data
-    emoji_font_loaded: bool, // Flag to track if the emoji font is loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Clear(ClearTarget),
+}
+
+impl Pending {
+    /**
+    Question the confirmation overlay asks
+    @param self: Pending action
+    @return String: Question to answer with yes or no
+    */
+    fn question(self) -> String {
+        match self {
+            Pending::Clear(target) => {
+                format!("Clear all {}? This can't be undone.", target.label())
+            }
+        }
+    }
 }
 
 /**
-Define the messages the application can react to (none for now)
+Define the messages the application can react to
 */
 #[derive(Debug, Clone)]
 enum Message {
     FontLoaded(Result<(), font::Error>), // Message to signal font loading result
+    RandomPick,                          // Copy a random emoji from the visible set
+    CycleSortMode,                       // Switch to the next grid ordering
+    CycleSearchScope,                    // Switch which fields search looks at
+    MoveFocus(Direction),                // Move the keyboard focus through the results
+    KeyReleased,                         // A key was let go, ends a held focus move
+    ActivateFocus,                       // Copy the emoji with keyboard focus
+    CopyHovered,                         // Copy the hovered emoji, else the focused one
+    NextPage,                            // Go forward a page in paged mode
+    PrevPage,                            // Go back a page in paged mode
+    EmojiClicked(usize),                 // Emoji clicked with the mouse, handled per click mode
+    EmojiSelected(usize),                // Emoji activated, copy it as a glyph
+    OpenContextMenu(usize),              // Emoji right-clicked, show the copy-as menu
+    CloseContextMenu,                    // Dismiss the copy-as menu
+    CopyAs(usize, Representation),       // Copy an emoji in a chosen representation
+    CopyWithTone(usize, SkinTone),       // Copy an emoji with a skin tone from the detail panel
+    SelectView(View),                    // Switch the grid to a category, recents or favorites
+    ApplyCategoryHint(String),           // Clear the search and open the hinted category
+    Scrolled(scrollable::Viewport),      // Grid scrolled, remember where we are
+    QueryChanged(String),                // Search box edited
+    SearchSubmit,                        // Enter pressed in the search box, copy the top result
+    ToggleHelp,                          // Show or hide the keyboard shortcut overlay
+    ToggleLog,                           // Show or hide the log viewer
+    LogFilterChanged(String),            // Log viewer filter box edited
+    LogLevelChanged(Level),              // Lowest level shown in the log viewer picked
+    ClearLog,                            // Forget the lines kept for the log viewer
+    Clear(ClearTarget),                  // Clear part of the user data, asking first if configured
+    ConfirmResult(bool),                 // Answer from the confirmation overlay
+    OpenSettings,                        // Show the settings overlay
+    PreviewTheme(ThemeMode),             // Theme picked in the settings, not applied yet
+    ApplySettings,                       // Apply the previewed theme and close the settings
+    CancelSettings,                      // Close the settings, keeping the current theme
+    ToggleLayout,                        // Switch between grid and list layout
+    Dismiss,                             // Close whichever overlay or menu is open
+    FirstFrame,                          // Window has drawn, safe to start the font load
+    ToggleFavorite(usize),               // Pin or unpin an emoji as a favorite
+    MoveFavorite(usize, Direction),      // Move a favorite within the favorites order
+    MoveFocusedFavorite(Direction),      // Move the focused emoji within the favorites view
+    ToggleCompare(usize),                // Add or remove an emoji from the compare panel
+    CompareSelection,                    // Put the selected emojis side by side
+    Tick(Instant),                       // Periodic tick for deferred work like saving
+    ShowMore,                            // Render another batch of capped results
+    OpenVariants(usize),                 // Emoji with variants clicked, show the picker
+    CopyVariant(usize, String),          // Copy a specific glyph of an emoji, e.g. a variant
+    HoverEnter(usize),                   // Cursor moved onto an emoji
+    HoverExit(usize),                    // Cursor moved off an emoji
+    AnimationFrame(Instant),             // Redraw tick while an animated preview is playing
+    WindowResized(f32),                  // Window width changed, recompute the grid columns
+    Activity,                            // Keyboard or mouse input, resets the idle timeout
+    CheckAppearance,                     // Time to ask the desktop for its light or dark preference
+    AppearanceDetected(Option<Appearance>), // Desktop preference, None if it can't be read
+    ModifiersChanged(keyboard::Modifiers), // Modifier keys pressed or released
+    CopySelection,                       // Copy every selected emoji at once
+    PrimaryWritten(Result<(), String>),  // Primary selection written, or why not
+    ClipboardStashed(Option<String>, String), // Clipboard contents before a copy, and what to copy
+    ClipboardChecked(Option<String>, String), // Clipboard after a copy, and what should be on it
+    UndoCopy,                            // Put back what the clipboard held before the last copy
+    ExportCopyLog,                       // Copy the session's copy log, one line per copy
+    DebugSnapshot,                       // Write the on-screen state to a file for bug reports
+    Quit,                                // Save and close the window
+    ToggleHiddenCategories,              // Show or hide the categories the config filters out
+    ToggleNewOnly,                       // Show only the newest emojis, or everything again
+    DataLoadProgress(usize, usize),      // (parsed, total) bytes of the data file so far
+    DataLoaded(Result<Vec<EmojiData>, String>), // Data file finished loading, or is incompatible
+    TagDraftChanged(String),             // Tag box in the copy-as menu edited
+    AddTag(usize),                       // Tag an emoji with the drafted tag
+    RemoveTag(usize, String),            // Remove a tag from an emoji
+    SelectTag(Option<String>),           // Only show emojis with this tag, None for all
 }
 
 /**
 Load the font bytes for an emoji font, for now hardcoding to Noto Color Emoji
+- Only bundled with the `bundled-font` feature, otherwise the font comes from `font_path` in the config
 */
+#[cfg(feature = "bundled-font")]
 const NOTO_COLOR_EMOJI_BYTES: &[u8] = include_bytes!("../assets/NotoColorEmoji-Regular.ttf");
 
 /**
@@ -46,6 +274,210 @@ Constant for the emoji font
 */
 const EMOJI_FONT: Font = Font::with_name("Noto Color Emoji");
 
+/**
+How long user data changes are batched up before being written to disk
+*/
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(2);
+
+/**
+How long the window stays open after a copy with PostCopy::CloseDelay
+*/
+const POST_COPY_DELAY: Duration = Duration::from_millis(800);
+
+/**
+How long the copied cell flashes for
+*/
+const FLASH_DURATION: Duration = Duration::from_millis(400);
+
+/**
+How long new results take to slide into place when animations are on
+*/
+const SLIDE_DURATION: Duration = Duration::from_millis(150);
+
+/**
+How far below their place new results start sliding from
+*/
+const SLIDE_DISTANCE: f32 = 12.0;
+
+/**
+Maximum gap between two clicks on the same emoji to count as a double-click
+*/
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/**
+Font load retries, each waits twice as long as the last plus up to one base delay of jitter
+*/
+const FONT_RETRY_LIMIT: u32 = 3;
+const FONT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/**
+Placeholder spinner frames, plain ASCII so any font can draw them
+*/
+const SPINNER_FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+const SPINNER_INTERVAL: Duration = Duration::from_millis(120);
+
+/**
+Longest placeholder label, anything longer gets cut off
+*/
+const MAX_PLACEHOLDER_CHARS: usize = 8;
+
+/**
+How many emojis are rendered at first in scroll mode, and how many more each "show more" adds
+*/
+const RESULT_LIMIT_STEP: usize = 200;
+
+/**
+Grid layout constants
+*/
+const EMOJI_SIZE: u16 = 32;
+const CELL_BORDER: f32 = 2.0; // Flash border around each cell
+const LINE_HEIGHT: f32 = 1.3; // Iced's default line height, relative to the text size
+const SECTION_TITLE_HEIGHT: f32 = 14.0 * LINE_HEIGHT; // Favorites and recents titles
+const SPACING: u16 = 10;
+
+/**
+How often the desktop's light or dark preference is checked in system theme mode
+*/
+const APPEARANCE_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/**
+Most emojis the compare panel holds, and the size they're drawn at
+*/
+const MAX_COMPARE: usize = 4;
+const COMPARE_SIZE: u16 = 64;
+
+/**
+Most entries kept in the session's copy log
+*/
+const MAX_COPY_LOG: usize = 500;
+
+/**
+Results listed in a debug snapshot, the count covers the rest
+*/
+const SNAPSHOT_RESULTS: usize = 50;
+
+/**
+Copies at most this far apart count as copied together for the suggestions
+*/
+const PAIR_WINDOW: Duration = Duration::from_secs(60);
+
+/**
+Most suggestions shown in the "Often used with" row
+*/
+const MAX_SUGGESTIONS: usize = 8;
+
+/**
+Emojis drawn in each theme preview of the settings overlay
+*/
+const SETTINGS_SAMPLES: usize = 4;
+
+/**
+Longest keyword or category shown in list rows, the detail panel has the full text
+*/
+const MAX_LABEL_CHARS: usize = 40;
+
+/**
+Longest preview of the copied text in the toast after copying a selection
+*/
+const COPY_PREVIEW_CHARS: usize = 24;
+
+/**
+Tallest the keywords in the detail panel get before they scroll
+*/
+const DETAIL_MAX_HEIGHT: f32 = 80.0;
+
+/**
+Initial window width, the grid columns follow the width from there
+*/
+const WINDOW_WIDTH: f32 = 400.0;
+
+/**
+Starting window height, before max_height_share gets a say
+*/
+const WINDOW_HEIGHT: f32 = 200.0;
+
+/**
+Queries the --bench mode times, from browsing everything to a rare word
+*/
+const BENCH_QUERIES: [&str; 4] = ["", "a", "face", "zebra"];
+
+/**
+How many times each query is run for --bench, the average is reported
+*/
+const BENCH_ROUNDS: u32 = 50;
+
+/**
+Everything visible_emojis depends on besides the data itself, which data_generation stands in for
+- Only the indices are memoized, not the rows laid out from them, so window size and theme aren't part of it
+*/
+#[derive(Debug, Clone, PartialEq)]
+struct VisibleKey {
+    query: String,
+    view: View,
+    tag_filter: Option<String>,
+    new_only: bool,
+    sort_mode: SortMode,
+    search_scope: SearchScope,
+    case_sensitive: bool,
+    whole_word_below: usize,
+    search_follows_view: bool,
+    data_generation: u64,
+}
+
+/**
+What the clipboard held before the last copy, so UndoCopy can put it back
+- Only the latest copy can be undone, every copy replaces whatever was stashed before it
+*/
+#[derive(Debug, Default)]
+struct ClipboardStash {
+    previous: Option<String>,
+}
+
+impl ClipboardStash {
+    /**
+    Remember the clipboard contents read just before a copy
+    - An empty clipboard has nothing to put back, so it leaves nothing to undo
+    @param &mut self: Mutable self reference
+    @param previous: Clipboard contents before the copy, None if it was empty
+    */
+    fn stash(&mut self, previous: Option<String>) {
+        self.previous = previous;
+    }
+
+    /**
+    Take the stashed contents to write back, an undo can't be repeated
+    @param &mut self: Mutable self reference
+    @return Option<String>: Contents from before the last copy, None if there's nothing to undo
+    */
+    fn undo(&mut self) -> Option<String> {
+        self.previous.take()
+    }
+}
+
+/**
+What a single Escape press closes or clears, see next_dismissal()
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Dismissal {
+    Question,  // Pending yes or no question
+    Help,      // Keyboard shortcut overlay
+    Log,       // Log viewer
+    Settings,  // Settings overlay
+    Menu,      // Copy-as menu and variant picker
+    Compare,   // Emojis lined up for comparison
+    Selection, // Emojis picked for a multi-copy
+    Query,     // Search box contents
+    Window,    // Nothing else is left, so the window itself
+}
+
+/**
+Options from the command line, handed to the application when it starts
+*/
+#[derive(Debug, Default)]
+struct StartupFlags {
+    fresh: bool, // Start without restoring data from earlier runs
+}
+
 /**
 Implementation of the Application trait for our state
 */
@@ -53,43 +485,15 @@ impl Application for NicePickApp {
     type Executor = executor::Default;
     type Message = Message;
     type Theme = Theme;
-    type Flags = ();
+    type Flags = StartupFlags;
 
     /**
     Initialize the application state and load emoji data.
-    @params _flags: Flags for the application
+    @params flags: Flags for the application
     @return (Self, Command<Message>) Initialize the application state and load emoji data.
     */
-    fn new(_flags: ()) -> (Self, Command<Message>) {
-        // If debug logging is enabled, record the JSON load time
-        dbug!("Initializing NicePickApp state (requesting font load)...");
-        let start_time = if logging::log_enabled(Level::Debug) {
-            Some(std::time::Instant::now())
-        } else {
-            None
-        };
-
-        // Load and parse JSON emoji data
-        let json_data = include_str!("../data.json");
-        let emojis: Vec<EmojiData> =
-            serde_json::from_str(json_data).expect("Failed to parse data.json");
-
-        // Count final emoji JSON data load time (if debug logging is enabled)
-        if let Some(start) = start_time {
-            let duration = start.elapsed();
-            dbug!("JSON emoji data loaded in {:?}", duration);
-        }
-
-        info!("JSON emoji data loaded successfully");
-
-        // Loaded emojis get stored in app state
-        (
-            NicePickApp {
-                emojis,
-                emoji_font_loaded: false, // Font is not loaded initially
-            },
-            font::load(Cow::Borrowed(NOTO_COLOR_EMOJI_BYTES)).map(Message::FontLoaded),
-        )
+    fn new(flags: StartupFlags) -> (Self, Command<Message>) {
+        NicePickApp::with_config(Config::load(), flags)
     }
 
     /**
@@ -114,109 +518,4196 @@ impl Application for NicePickApp {
                 info!("Emoji font loaded successfully.");
                 Command::none()
             }
+            Message::FirstFrame => {
+                if self.font_requested {
+                    return Command::none();
+                }
+                self.font_requested = true;
+                dbug!("First frame drawn, requesting font load");
+                self.load_font()
+            }
             Message::FontLoaded(Err(e)) => {
-                fail!("Failed to load emoji font: {:?}", e);
-                // Keep emoji_font_loaded as false
+                // Loading can fail transiently, back off and try again a few times
+                if self.font_retry_count < FONT_RETRY_LIMIT {
+                    self.font_retry_count += 1;
+                    let backoff = FONT_RETRY_BASE_DELAY * 2u32.pow(self.font_retry_count - 1);
+                    let jitter = self.rng.index(FONT_RETRY_BASE_DELAY.as_millis() as usize);
+                    let delay = backoff + Duration::from_millis(jitter as u64);
+                    warn!(
+                        "Failed to load emoji font: {:?}, retry {} of {} in {:?}",
+                        e, self.font_retry_count, FONT_RETRY_LIMIT, delay
+                    );
+                    self.font_retry_at = Some(Instant::now() + delay);
+                } else {
+                    fail!(
+                        "Failed to load emoji font after {} retries: {:?}, using system fonts",
+                        FONT_RETRY_LIMIT,
+                        e
+                    );
+                    self.font_gave_up = true;
+                    self.push_toast(
+                        "Emoji font failed to load, using system fonts".to_string(),
+                        ToastLevel::Error,
+                    );
+                }
                 Command::none()
             }
-        }
-    }
-
-    /**
-    Application view function
-    @param &self: Self reference
-    @return Element<Message>: Element to display the application state
-    */
-    fn view(&self) -> Element<Message> {
-        // Start timer for view function if debug logging is enabled
-        let start_time = if logging::log_enabled(Level::Debug) {
-            Some(std::time::Instant::now())
-        } else {
-            None
-        };
-        const ITEMS_PER_ROW: usize = 4;
-        const SPACING: u16 = 10;
-
-        // Create rows of emojis
-        let mut rows = Vec::new();
-        for chunk in self.emojis.chunks(ITEMS_PER_ROW) {
-            let mut row_elements: Row<'_, Message, Theme, Renderer> = Row::new().spacing(SPACING);
-            for item in chunk {
-                // Add each emoji as text with the correct font
-                let emoji_text = if self.emoji_font_loaded {
-                    // Use the emoji font if loaded
-                    text(&item.emoji).font(EMOJI_FONT).size(32)
-                } else {
-                    // Use a placeholder or default font if not loaded yet
-                    text("⏳").size(32)
+            Message::RandomPick => {
+                // Fall back to the whole dataset if nothing is currently visible
+                let mut candidates = self.visible_emojis();
+                if candidates.is_empty() {
+                    candidates = (0..self.emojis.len()).collect();
+                }
+                let Some(index) = self.rng.pick(&candidates) else {
+                    warn!("Random pick requested but there are no emojis loaded");
+                    return Command::none();
                 };
-                row_elements = row_elements.push(emoji_text);
+                info!("Randomly picked {}", self.emojis[index].keywords);
+                self.copy_emoji(index, self.copy_as)
             }
-            rows.push(row_elements);
-        }
-
-        // Create a column containing all the rows
-        let content = Column::with_children(rows.into_iter().map(Element::from))
-            .spacing(SPACING)
-            .padding(SPACING); // Add padding around the grid
-
-        // Wrap the content in a scrollable container
-        let scrollable_content = scrollable(content).width(Length::Fill).height(Length::Fill);
-
-        // Wrap the scrollable in a container for background and centering
-        let final_element = container(scrollable_content)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .center_x()
-            .center_y()
-            .style(|_theme: &Theme| container::Appearance {
-                background: Some(Color::from_rgb8(40, 44, 52).into()),
-                ..container::Appearance::default()
-            })
-            .into();
-
-        // Log duration if debug logging is enabled
-        if let Some(start) = start_time {
-            let duration = start.elapsed();
-            dbug!("View construction took {:?}", duration);
-        }
-
-        final_element
-    }
-
-    fn theme(&self) -> Theme {
-        Theme::default()
-    }
-}
-
-/**
-Main entrypoint of the application
-@returns Iced application
-*/
-fn main() -> iced::Result {
-    let main_start_time = std::time::Instant::now();
-
-    // Initialize logging
-    logging::init(Level::Debug);
-
-    dbug!("Logger initialized in {:?}", main_start_time.elapsed());
+            Message::CycleSortMode => {
+                self.sort_mode = self.sort_mode.next();
+                // A new ordering makes the current page and scroll positions meaningless
+                self.page = 0;
+                self.scroll_offsets.clear();
+                info!("Sort mode changed to {:?}", self.sort_mode);
+                Command::none()
+            }
+            Message::CycleSearchScope => {
+                self.search_scope = self.search_scope.next();
+                self.start_slide();
+                self.page = 0;
+                self.scroll_offsets.clear();
+                info!("Search scope changed to {:?}", self.search_scope);
+                Command::none()
+            }
+            Message::MoveFocus(direction) => {
+                // Key repeat from a held key only gets through once per nav_repeat
+                if let Some(interval) = self.nav_repeat {
+                    let now = Instant::now();
+                    if self.last_nav.is_some_and(|(last, at)| {
+                        last == direction && now.duration_since(at) < interval
+                    }) {
+                        return Command::none();
+                    }
+                    self.last_nav = Some((direction, now));
+                }
+                let visible = self.visible_emojis();
+                if visible.is_empty() {
+                    return Command::none();
+                }
+                let last = visible.len() - 1;
+                let step = match self.layout {
+                    Layout::Grid => self.columns,
+                    Layout::List => 1,
+                };
 
-    info!("Configuring application settings");
+                // Paging moves by as many whole rows as fit on screen, or by a page in paged mode
+                let page_step = match self.scroll_mode {
+                    ScrollMode::Paged => self.per_page(),
+                    ScrollMode::Scroll => {
+                        let rows = self.viewport.map_or(1.0, |(height, _)| {
+                            (height / self.row_stride(EMOJI_SIZE)).floor().max(1.0)
+                        });
+                        rows as usize * step
+                    }
+                };
 
-    let settings = Settings {
-        window: window::Settings {
-            size: Size::new(400.0, 200.0),
-            decorations: false,
-            transparent: true,
-            ..window::Settings::default()
-        },
-        // Let Iced use its default text font
-        ..Settings::default()
-    };
+                // The first move, or one after the focused emoji got filtered away, starts at the top
+                let current = self
+                    .focus
+                    .and_then(|focus| visible.iter().position(|&i| i == focus));
+                let position = match (current, direction) {
+                    (_, Direction::First) => 0,
+                    (_, Direction::Last) => last,
+                    (None, _) => 0,
+                    (Some(position), Direction::PageUp) => position.saturating_sub(page_step),
+                    (Some(position), Direction::PageDown) => (position + page_step).min(last),
+                    (Some(position), Direction::Left) => position.saturating_sub(1),
+                    (Some(position), Direction::Right) => (position + 1).min(last),
+                    (Some(position), Direction::Up) if position >= step => position - step,
+                    (Some(position), Direction::Down) if position + step <= last => position + step,
+                    (Some(position), _) => position,
+                };
+                self.focus = Some(visible[position]);
+                self.reveal(position)
+            }
+            Message::KeyReleased => {
+                self.last_nav = None;
+                Command::none()
+            }
+            Message::ActivateFocus => {
+                match self
+                    .focus
+                    .filter(|focus| self.visible_emojis().contains(focus))
+                {
+                    Some(index) => self.update(Message::EmojiSelected(index)),
+                    None => Command::none(),
+                }
+            }
+            Message::CopyHovered => {
+                // A hovered emoji that scrolled out of the results gives way to the focus
+                let visible = self.visible_emojis();
+                let is_visible = |index: &usize| visible.contains(index);
+                match self
+                    .hovered
+                    .filter(is_visible)
+                    .or(self.focus.filter(is_visible))
+                {
+                    Some(index) => self.update(Message::EmojiSelected(index)),
+                    None => Command::none(),
+                }
+            }
+            Message::NextPage => {
+                if self.scroll_mode == ScrollMode::Paged
+                    && self.page + 1 < self.page_count(self.visible_emojis().len())
+                {
+                    self.page += 1;
+                    dbug!("Moved to page {}", self.page);
+                }
+                Command::none()
+            }
+            Message::PrevPage => {
+                if self.scroll_mode == ScrollMode::Paged && self.page > 0 {
+                    self.page -= 1;
+                    dbug!("Moved to page {}", self.page);
+                }
+                Command::none()
+            }
+            Message::EmojiClicked(index) if self.modifiers.command() => {
+                // Ctrl+click builds up a selection instead of copying
+                if let Some(position) = self.selection.iter().position(|&i| i == index) {
+                    self.selection.remove(position);
+                } else {
+                    self.selection.push(index);
+                }
+                dbug!("{} emojis selected", self.selection.len());
+                Command::none()
+            }
+            Message::EmojiClicked(index) if self.modifiers.shift() => {
+                // Shift+click copies the text form of glyphs that have one
+                self.context_menu = None;
+                self.copy_presentation(index, Presentation::Text)
+            }
+            Message::EmojiClicked(index) => {
+                let now = Instant::now();
+                let previous = self.last_click.replace((index, now));
+                match self.click_mode {
+                    ClickMode::SingleCopy => {
+                        self.context_menu = None;
+                        self.copy_presentation(index, Presentation::Emoji)
+                    }
+                    ClickMode::DoubleCopy => {
+                        let is_double = previous.is_some_and(|(last, at)| {
+                            last == index && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+                        });
+                        if is_double {
+                            // Don't let a third click count as another double-click
+                            self.last_click = None;
+                            self.context_menu = None;
+                            self.copy_presentation(index, Presentation::Emoji)
+                        } else {
+                            self.detail = Some(index);
+                            Command::none()
+                        }
+                    }
+                }
+            }
+            Message::EmojiSelected(index) => {
+                self.context_menu = None;
+                self.copy_emoji(index, self.copy_as)
+            }
+            Message::OpenVariants(index) => {
+                self.context_menu = None;
+                self.variant_picker = Some(index);
+                Command::none()
+            }
+            Message::CopyVariant(index, glyph) => {
+                self.context_menu = None;
+                self.variant_picker = None;
+                self.copy_glyph(index, &glyph, self.copy_as)
+            }
+            Message::CopyWithTone(index, tone) => {
+                let toned = skin_tone::apply(&self.emojis[index].emoji, tone);
+                self.copy_glyph(index, &toned, self.copy_as)
+            }
+            Message::OpenContextMenu(index) => {
+                self.context_menu = Some(index);
+                Command::none()
+            }
+            Message::CloseContextMenu => {
+                self.context_menu = None;
+                Command::none()
+            }
+            Message::CopyAs(index, representation) => {
+                self.context_menu = None;
+                self.copy_emoji(index, representation)
+            }
+            Message::ApplyCategoryHint(category) => {
+                info!("Taking the hint, opening the {} category", category);
+                self.query.clear();
+                self.update(Message::SelectView(View::Category(category)))
+            }
+            Message::SelectView(view) => {
+                if view == self.view {
+                    return Command::none();
+                }
+                info!("Switching view to {:?}", view);
+                self.view = view;
+                self.page = 0;
+                self.start_slide();
 
-    let setup_duration = main_start_time.elapsed();
-    dbug!("Application setup (before run) took {:?}", setup_duration);
-    info!("Starting Iced event loop (NicePickApp::run)...");
+                // Jump back to wherever we left this view, or the top if it's new
+                let offset = self.scroll_offsets.get(&self.view).copied().unwrap_or(0.0);
+                scrollable::scroll_to(
+                    grid_scroll_id(),
+                    scrollable::AbsoluteOffset { x: 0.0, y: offset },
+                )
+            }
+            Message::QueryChanged(query) => {
+                // Results change completely, so old pages and scroll positions no longer apply
+                self.query = query;
+                self.page = 0;
+                self.result_limit = RESULT_LIMIT_STEP;
+                self.start_slide();
+                self.scroll_offsets.clear();
+                Command::none()
+            }
+            Message::ShowMore => {
+                self.result_limit += RESULT_LIMIT_STEP;
+                dbug!("Result limit raised to {}", self.result_limit);
+                Command::none()
+            }
+            Message::SearchSubmit => {
+                // The focused result if there is one, the top result otherwise
+                let visible = self.visible_emojis();
+                let focused = self.focus.filter(|focus| visible.contains(focus));
+                let Some(index) = focused.or(visible.first().copied()) else {
+                    dbug!("No search results to copy for {:?}", self.query);
+                    return Command::none();
+                };
 
-    NicePickApp::run(settings)
+                let command = self.copy_emoji(index, self.copy_as);
+                // Either keep the query around for another tweak-and-copy or start fresh
+                if !self.retain_query {
+                    self.query.clear();
+                    self.page = 0;
+                    self.scroll_offsets.clear();
+                }
+                command
+            }
+            Message::ToggleLayout => {
+                self.layout = self.layout.toggle();
+                info!("Switched to {:?} layout", self.layout);
+                self.user_data.layout = Some(self.layout);
+                self.mark_unsaved();
+                self.page = 0;
+                Command::none()
+            }
+            Message::ToggleHelp => {
+                self.show_help = !self.show_help;
+                Command::none()
+            }
+            Message::ToggleLog => {
+                self.show_log = !self.show_log;
+                Command::none()
+            }
+            Message::LogFilterChanged(filter) => {
+                self.log_filter = filter;
+                Command::none()
+            }
+            Message::LogLevelChanged(level) => {
+                self.log_level = level;
+                Command::none()
+            }
+            Message::ClearLog => {
+                logging::clear_history();
+                Command::none()
+            }
+            Message::Clear(target) => {
+                if self.confirm_clears {
+                    self.pending = Some(Pending::Clear(target));
+                } else {
+                    self.run_pending(Pending::Clear(target));
+                }
+                Command::none()
+            }
+            Message::ConfirmResult(confirmed) => {
+                // A no drops the action for good, it has to be asked for again
+                match self.pending.take() {
+                    Some(pending) if confirmed => self.run_pending(pending),
+                    Some(pending) => dbug!("Cancelled {:?}", pending),
+                    None => {}
+                }
+                Command::none()
+            }
+            Message::OpenSettings => {
+                self.settings = Some(self.theme_mode);
+                Command::none()
+            }
+            Message::PreviewTheme(mode) => {
+                if self.settings.is_some() {
+                    self.settings = Some(mode);
+                }
+                // The system preview needs to know what the desktop prefers
+                if mode == ThemeMode::System && self.system_appearance.is_none() {
+                    return Command::perform(
+                        async { appearance::detect() },
+                        Message::AppearanceDetected,
+                    );
+                }
+                Command::none()
+            }
+            Message::ApplySettings => {
+                let Some(mode) = self.settings.take() else {
+                    return Command::none();
+                };
+                if mode != self.theme_mode {
+                    info!("Switched to the {} theme", mode.label());
+                    self.theme_mode = mode;
+                    self.user_data.theme = Some(mode);
+                    self.mark_unsaved();
+                }
+                Command::none()
+            }
+            Message::CancelSettings => {
+                self.settings = None;
+                Command::none()
+            }
+            Message::Dismiss => {
+                match self.next_dismissal() {
+                    Dismissal::Question => self.pending = None,
+                    Dismissal::Help => self.show_help = false,
+                    Dismissal::Log => self.show_log = false,
+                    Dismissal::Settings => self.settings = None,
+                    Dismissal::Menu => {
+                        self.context_menu = None;
+                        self.variant_picker = None;
+                    }
+                    Dismissal::Compare => self.compare.clear(),
+                    Dismissal::Selection => self.selection.clear(),
+                    Dismissal::Query => {
+                        info!("Clearing the search, dismissing again closes the window");
+                        return self.update(Message::QueryChanged(String::new()));
+                    }
+                    Dismissal::Window => {
+                        info!("Nothing left to dismiss, closing");
+                        return self.close_window();
+                    }
+                }
+                Command::none()
+            }
+            Message::CopySelection => self.copy_selection(),
+            Message::ClipboardStashed(previous, contents) => {
+                // Only the most recent stash is kept, an empty clipboard has nothing to put back
+                self.clipboard_stash.stash(previous);
+                // Read the clipboard back right after writing it, the runtime runs both in order
+                let expected = contents.clone();
+                Command::batch([
+                    clipboard::write(contents),
+                    clipboard::read(move |current| {
+                        Message::ClipboardChecked(current, expected.clone())
+                    }),
+                ])
+            }
+            Message::ClipboardChecked(current, expected) => {
+                let result = if current.as_deref() == Some(expected.as_str()) {
+                    Ok(())
+                } else {
+                    Err("the clipboard holds something else after writing it".to_string())
+                };
+                self.confirm_write("clipboard", result);
+                Command::none()
+            }
+            Message::PrimaryWritten(result) => {
+                self.confirm_write("primary selection", result);
+                Command::none()
+            }
+            Message::ExportCopyLog => {
+                if self.copy_log.is_empty() {
+                    self.push_toast("Nothing copied yet".to_string(), ToastLevel::Info);
+                    return Command::none();
+                }
+                info!("Exporting {} copy log entries", self.copy_log.len());
+                self.push_toast(
+                    format!(
+                        "Copied the log of {} copies",
+                        format::thousands(self.copy_log.len())
+                    ),
+                    ToastLevel::Success,
+                );
+                self.write_clipboard(self.format_copy_log())
+            }
+            Message::Quit => {
+                info!("Quitting");
+                self.close_window()
+            }
+            Message::DebugSnapshot => {
+                let snapshot = self.debug_snapshot();
+                let json = serde_json::to_string_pretty(&snapshot).unwrap_or_default();
+                let path = config::snapshot_path();
+                let written = storage::write_atomic(&path, json.as_bytes())
+                    .map(|()| path)
+                    .map_err(|e| e.to_string());
+                match written {
+                    Ok(path) => {
+                        info!("Debug snapshot written to {}", path.display());
+                        self.push_toast(
+                            format!("Snapshot saved to {}", path.display()),
+                            ToastLevel::Info,
+                        );
+                    }
+                    Err(e) => {
+                        // Still worth having, so it goes to stderr instead
+                        fail!("Failed to write debug snapshot: {}", e);
+                        eprintln!("{}", json);
+                        self.push_toast(
+                            "Snapshot written to stderr".to_string(),
+                            ToastLevel::Error,
+                        );
+                    }
+                }
+                Command::none()
+            }
+            Message::UndoCopy => match self.clipboard_stash.undo() {
+                Some(previous) => {
+                    info!("Restoring the clipboard from before the last copy");
+                    self.push_toast("Clipboard restored".to_string(), ToastLevel::Info);
+                    clipboard::write(previous)
+                }
+                None => {
+                    dbug!("Nothing to undo");
+                    Command::none()
+                }
+            },
+            Message::ToggleCompare(index) => {
+                let emoji = &self.emojis[index].emoji;
+                if let Some(position) = self.compare.iter().position(|glyph| glyph == emoji) {
+                    self.compare.remove(position);
+                } else if self.compare.len() < MAX_COMPARE {
+                    self.compare.push(emoji.clone());
+                } else {
+                    self.push_toast(
+                        format!("Compare holds at most {} emojis", MAX_COMPARE),
+                        ToastLevel::Info,
+                    );
+                }
+                self.context_menu = None;
+                Command::none()
+            }
+            Message::CompareSelection => {
+                // Whatever was being compared makes way for the selection
+                let selection = std::mem::take(&mut self.selection);
+                self.compare = selection
+                    .iter()
+                    .take(MAX_COMPARE)
+                    .map(|&index| self.emojis[index].emoji.clone())
+                    .collect();
+                if selection.len() > MAX_COMPARE {
+                    warn!(
+                        "Comparing the first {} of {} selected emojis",
+                        MAX_COMPARE,
+                        selection.len()
+                    );
+                }
+                Command::none()
+            }
+            Message::ToggleNewOnly => {
+                self.new_only = !self.new_only;
+                self.start_slide();
+                info!(
+                    "{} emojis",
+                    if self.new_only {
+                        "Showing new"
+                    } else {
+                        "Showing all"
+                    }
+                );
+                self.focus = None;
+                self.selection.clear();
+                Command::none()
+            }
+            Message::ToggleHiddenCategories => {
+                self.show_hidden_categories = !self.show_hidden_categories;
+                info!(
+                    "{} filtered categories",
+                    if self.show_hidden_categories {
+                        "Showing"
+                    } else {
+                        "Hiding"
+                    }
+                );
+                self.apply_category_filter();
+                Command::none()
+            }
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+                Command::none()
+            }
+            Message::ToggleFavorite(index) => {
+                self.context_menu = None;
+                let emoji = &self.emojis[index].emoji;
+                if self.user_data.toggle_favorite(emoji) {
+                    info!("Added {} to favorites", emoji);
+                } else {
+                    info!("Removed {} from favorites", emoji);
+                }
+                self.mark_unsaved();
+                Command::none()
+            }
+            Message::MoveFavorite(index, direction) => {
+                // Favorites may be stored as a variant of the emoji
+                let Some(from) = self
+                    .user_data
+                    .favorites
+                    .iter()
+                    .position(|favorite| self.index_of(favorite) == Some(index))
+                else {
+                    return Command::none();
+                };
+                let to = match direction {
+                    Direction::Left | Direction::Up => from.saturating_sub(1),
+                    Direction::Right | Direction::Down => from + 1,
+                    Direction::PageUp | Direction::First => 0,
+                    Direction::PageDown | Direction::Last => usize::MAX,
+                };
+                if self.user_data.move_favorite(from, to) {
+                    dbug!(
+                        "Moved favorite {} {:?}",
+                        self.emojis[index].emoji,
+                        direction
+                    );
+                    self.mark_unsaved();
+                }
+                Command::none()
+            }
+            Message::MoveFocusedFavorite(direction) => {
+                if self.view != View::Favorites {
+                    return Command::none();
+                }
+                match self
+                    .focus
+                    .filter(|focus| self.visible_emojis().contains(focus))
+                {
+                    Some(index) => self.update(Message::MoveFavorite(index, direction)),
+                    None => Command::none(),
+                }
+            }
+            Message::CheckAppearance => {
+                Command::perform(async { appearance::detect() }, Message::AppearanceDetected)
+            }
+            Message::AppearanceDetected(detected) => {
+                if detected != self.system_appearance {
+                    info!("Desktop appearance is now {:?}", detected);
+                    self.system_appearance = detected;
+                }
+                Command::none()
+            }
+            Message::Activity => {
+                self.last_activity = Instant::now();
+                Command::none()
+            }
+            Message::DataLoadProgress(parsed, total) => {
+                self.loading = Some((parsed, total));
+                Command::none()
+            }
+            Message::DataLoaded(result) => {
+                self.loading = None;
+                match result {
+                    Ok(emojis) => self.set_emojis(emojis),
+                    Err(e) => self.load_error = Some(e),
+                }
+                Command::none()
+            }
+            Message::WindowResized(width) => {
+                self.window_width = width;
+                let columns = column_count(
+                    width,
+                    self.min_columns,
+                    self.max_columns,
+                    cell_width(EMOJI_SIZE, self.cell_shape),
+                );
+                if columns != self.columns {
+                    dbug!("Window is {} wide, using {} columns", width, columns);
+                    self.columns = columns;
+                }
+                Command::none()
+            }
+            Message::TagDraftChanged(draft) => {
+                self.tag_draft = draft;
+                Command::none()
+            }
+            Message::AddTag(index) => {
+                let tag = std::mem::take(&mut self.tag_draft);
+                let emoji = &self.emojis[index].emoji;
+                if self.user_data.add_tag(emoji, &tag) {
+                    info!("Tagged {} with {:?}", emoji, tag.trim());
+                    self.mark_unsaved();
+                }
+                Command::none()
+            }
+            Message::RemoveTag(index, tag) => {
+                let emoji = &self.emojis[index].emoji;
+                self.user_data.remove_tag(emoji, &tag);
+                info!("Removed tag {:?} from {}", tag, emoji);
+                // Don't leave the grid filtered by a tag nothing has anymore
+                if self.tag_filter.as_ref() == Some(&tag)
+                    && !self.user_data.all_tags().contains(&tag)
+                {
+                    self.tag_filter = None;
+                }
+                self.mark_unsaved();
+                Command::none()
+            }
+            Message::SelectTag(tag) => {
+                info!("Filtering by tag {:?}", tag);
+                self.tag_filter = tag;
+                self.page = 0;
+                self.start_slide();
+                Command::none()
+            }
+            Message::Tick(now) => {
+                // A failed font load is retried from here so the backoff never blocks the UI
+                if self.font_retry_at.is_some_and(|at| now >= at) {
+                    self.font_retry_at = None;
+                    dbug!("Retrying emoji font load");
+                    return self.load_font();
+                }
+
+                // The flash only needs ticks until it has faded out
+                if self
+                    .flash
+                    .is_some_and(|(_, started)| now.duration_since(started) >= FLASH_DURATION)
+                {
+                    self.flash = None;
+                }
+                if self
+                    .filter_changed
+                    .is_some_and(|changed| now.duration_since(changed) >= SLIDE_DURATION)
+                {
+                    self.filter_changed = None;
+                }
+                self.toasts.retain(|toast| !toast.expired(now));
+
+                // Launcher style use, get out of the way after a copy or once nobody is using the window
+                if self.close_at.is_some_and(|at| now >= at) {
+                    info!("Copied, closing");
+                    return self.close_window();
+                }
+                if let Some(timeout) = self.idle_timeout {
+                    if now.duration_since(self.last_activity) >= timeout {
+                        info!("No input for {:?}, closing", timeout);
+                        return self.close_window();
+                    }
+                }
+
+                // Write once changes have settled, so bursts of copies become a single save
+                if let Some(since) = self.unsaved_since {
+                    if now.duration_since(since) >= SAVE_DEBOUNCE {
+                        self.unsaved_since = None;
+                        match self.save_user_data() {
+                            Ok(()) => dbug!("User data saved"),
+                            Err(e) => fail!("Failed to save user data: {}", e),
+                        }
+                    }
+                }
+                Command::none()
+            }
+            Message::HoverEnter(index) => {
+                // Re-entering the same cell, e.g. after a redraw, changes nothing
+                if self.hovered == Some(index) {
+                    return Command::none();
+                }
+                self.hovered = Some(index);
+                self.detail = Some(index);
+
+                // Only the hovered emoji gets decoded, anything else is dropped
+                #[cfg(feature = "animated-preview")]
+                {
+                    let emoji = &self.emojis[index].emoji;
+                    if self.animation.as_ref().is_none_or(|a| &a.emoji != emoji) {
+                        self.animation = self
+                            .animation_dir
+                            .as_deref()
+                            .and_then(|dir| animation::Animation::load(dir, emoji));
+                    }
+                }
+                Command::none()
+            }
+            Message::HoverExit(index) => {
+                // Entering the next cell may arrive before leaving this one
+                if self.hovered == Some(index) {
+                    self.hovered = None;
+                    #[cfg(feature = "animated-preview")]
+                    {
+                        self.animation = None;
+                    }
+                }
+                Command::none()
+            }
+            Message::AnimationFrame(_now) => {
+                #[cfg(feature = "animated-preview")]
+                if let Some(animation) = &mut self.animation {
+                    animation.advance(_now);
+                }
+                Command::none()
+            }
+            Message::Scrolled(viewport) => {
+                self.scroll_offsets
+                    .insert(self.view.clone(), viewport.absolute_offset().y);
+                self.viewport = Some((viewport.bounds().height, viewport.content_bounds().height));
+                Command::none()
+            }
+        }
+    }
+
+    /**
+    Application subscription function
+    @param &self: Self reference
+    @return Subscription<Message>: Keyboard events mapped to messages
+    */
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            keyboard::on_key_press(handle_key_press),
+            event::listen_with(handle_window_event),
+        ];
+        // Iced can't tell us when the desktop switches between light and dark, so keep asking
+        if self.theme_mode == ThemeMode::System {
+            subscriptions.push(
+                iced::time::every(APPEARANCE_POLL_INTERVAL).map(|_| Message::CheckAppearance),
+            );
+        }
+        // Releases only matter for ending a throttled focus move
+        if self.nav_repeat.is_some() {
+            subscriptions.push(keyboard::on_key_release(|_, _| Some(Message::KeyReleased)));
+        }
+        // Input only needs watching when it can time out
+        if self.idle_timeout.is_some() {
+            subscriptions.push(event::listen_with(handle_activity_event));
+        }
+        // Runs until the data file has loaded, then gets dropped
+        if let (Some(path), Some(_)) = (&self.data_path, self.loading) {
+            subscriptions.push(load_data(
+                path.clone(),
+                self.data_url.clone(),
+                self.dedup_key,
+            ));
+        }
+        // Only listen for frames until the font load has been started
+        if !self.font_requested {
+            subscriptions.push(window::frames().map(|_| Message::FirstFrame));
+        }
+        // Drive the animated preview only while one is playing
+        #[cfg(feature = "animated-preview")]
+        if self.animation.is_some() {
+            subscriptions.push(window::frames().map(Message::AnimationFrame));
+        }
+        // Tick quickly while a flash is fading or the spinner turns, slowly while something waits to be saved
+        let spinning = !self.emoji_font_loaded && !self.font_gave_up && self.placeholder.is_none();
+        let tick_interval = if self.flash.is_some() || self.filter_changed.is_some() {
+            Some(Duration::from_millis(30))
+        } else if self.close_at.is_some() {
+            Some(Duration::from_millis(50))
+        } else if spinning {
+            Some(SPINNER_INTERVAL)
+        } else if !self.toasts.is_empty()
+            || self.unsaved_since.is_some()
+            || self.font_retry_at.is_some()
+        {
+            Some(Duration::from_millis(500))
+        } else if self.idle_timeout.is_some() {
+            Some(Duration::from_secs(1))
+        } else {
+            None
+        };
+        if let Some(interval) = tick_interval {
+            subscriptions.push(iced::time::every(interval).map(Message::Tick));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    /**
+    Application view function
+    @param &self: Self reference
+    @return Element<Message>: Element to display the application state
+    */
+    fn view(&self) -> Element<Message> {
+        // Start timer for view function if debug logging is enabled
+        let start_time = if logging::log_enabled(Level::Debug) {
+            Some(std::time::Instant::now())
+        } else {
+            None
+        };
+        // Without usable data there's nothing to show but the error
+        if let Some(error) = &self.load_error {
+            let message = Column::new()
+                .spacing(SPACING)
+                .padding(SPACING)
+                .push(text("Couldn't load the emoji data").size(20))
+                .push(text(error))
+                .push(text("Update nicepick or point data_path at a compatible file").size(12));
+            return self.wrap_background(message.into());
+        }
+
+        // Large data files take a moment, show how far along they are
+        if let Some((parsed, total)) = self.loading {
+            let mut status = Column::new().spacing(SPACING).padding(SPACING);
+            status = if total == 0 {
+                // Still reading the file, there's no size to report yet
+                status.push(text("Reading emoji data..."))
+            } else {
+                status
+                    .push(text(format!(
+                        "Parsed {} of {} KB of emoji data...",
+                        format::thousands(parsed.div_ceil(1024)),
+                        format::thousands(total.div_ceil(1024))
+                    )))
+                    .push(progress_bar(0.0..=total as f32, parsed as f32))
+            };
+            return self.wrap_background(status.into());
+        }
+
+        // Overlays replace the whole window content while open, a pending question above all
+        if let Some(pending) = self.pending {
+            return self.wrap_background(confirm_view(&pending.question()));
+        }
+        if self.show_help {
+            return self.wrap_background(self.help_view());
+        }
+        if self.show_log {
+            return self.wrap_background(self.log_view());
+        }
+        if let Some(previewed) = self.settings {
+            return self.wrap_background(self.settings_view(previewed));
+        }
+
+        // In paged mode only the emojis on the current page get laid out
+        let visible = self.visible_emojis();
+        let page_count = self.page_count(visible.len());
+        let page = self.page.min(page_count - 1);
+        let shown = match self.scroll_mode {
+            // Scrolling is capped so huge result sets don't build thousands of widgets
+            ScrollMode::Scroll => &visible[..visible.len().min(self.result_limit)],
+            ScrollMode::Paged => {
+                let per_page = self.per_page();
+                let start = page * per_page;
+                &visible[start..(start + per_page).min(visible.len())]
+            }
+        };
+
+        // Favorites, recents and suggestions sit above the grid when browsing everything, section_gap apart
+        // Only sections that are shown get pushed, so an empty one leaves no extra gap behind
+        let mut content = Column::new().spacing(self.section_gap).padding(SPACING);
+        let browsing_all = self.active_query().is_empty() && self.view == View::All;
+        if browsing_all && (self.scroll_mode == ScrollMode::Scroll || page == 0) {
+            for (title, glyphs, size) in self.browse_sections() {
+                // Entries are stored as copied, so variants show up as themselves
+                let entries: Vec<(usize, &str)> = glyphs
+                    .into_iter()
+                    .filter_map(|glyph| Some((self.index_of(glyph)?, glyph)))
+                    .collect();
+                if !entries.is_empty() {
+                    content = content.push(
+                        Column::new()
+                            .spacing(SPACING)
+                            .push(text(title).size(14))
+                            .push(self.emoji_entries(entries, size)),
+                    );
+                }
+            }
+        }
+        // New results slide up into place, the animation only ever moves them, never holds input
+        let slide = self.filter_changed.map_or(0.0, |changed| {
+            let progress = changed.elapsed().as_secs_f32() / SLIDE_DURATION.as_secs_f32();
+            SLIDE_DISTANCE * (1.0 - progress.min(1.0))
+        });
+        let rows = container(self.emoji_rows(shown)).padding(Padding {
+            top: slide,
+            ..Padding::ZERO
+        });
+        let mut grid = Column::new().spacing(SPACING).push(rows);
+        let hidden = visible.len() - shown.len();
+        if self.scroll_mode == ScrollMode::Scroll && hidden > 0 {
+            grid = grid.push(
+                button(text(format!(
+                    "Show {} more",
+                    format::thousands(hidden.min(RESULT_LIMIT_STEP))
+                )))
+                .on_press(Message::ShowMore),
+            );
+        }
+        let content = content.push(grid);
+
+        let body: Element<Message> = match self.scroll_mode {
+            // Wrap the content in a scrollable container
+            ScrollMode::Scroll => scrollable(content)
+                .id(grid_scroll_id())
+                .on_scroll(Message::Scrolled)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into(),
+            // Show the page with navigation buttons underneath
+            ScrollMode::Paged => {
+                let navigation = row![
+                    button(text("<")).on_press_maybe((page > 0).then_some(Message::PrevPage)),
+                    text(format!(
+                        "Page {} of {}",
+                        format::thousands(page + 1),
+                        format::thousands(page_count)
+                    )),
+                    button(text(">"))
+                        .on_press_maybe((page + 1 < page_count).then_some(Message::NextPage)),
+                ]
+                .spacing(SPACING)
+                .align_items(Alignment::Center);
+
+                Column::new()
+                    .push(container(content).height(Length::Fill))
+                    .push(container(navigation).width(Length::Fill).center_x())
+                    .padding(SPACING)
+                    .into()
+            }
+        };
+
+        // Search and category tabs above the grid, or below everything with the search box last
+        let mut header = vec![self.search_bar(visible.len()), self.category_bar()];
+        // A search that found little but looks like a category name offers to open that category
+        let hint = search::category_hint(self.active_query(), &self.categories, visible.len())
+            .filter(|&category| self.view != View::Category(category.to_string()));
+        if let Some(category) = hint {
+            header.insert(
+                1,
+                container(
+                    button(text(format!("Did you mean the '{}' category?", category)).size(12))
+                        .style(theme::Button::Text)
+                        .on_press(Message::ApplyCategoryHint(category.to_string())),
+                )
+                .padding([0, SPACING])
+                .into(),
+            );
+        }
+        if !self.user_data.tags.is_empty() {
+            header.push(self.tag_bar());
+        }
+        let mut layout = Column::new();
+        if self.search_position == SearchPosition::Top {
+            for element in header.drain(..) {
+                layout = layout.push(element);
+            }
+        }
+        // How much of the result set is on screen
+        let status = text(format!(
+            "Showing {} of {}",
+            format::thousands(shown.len()),
+            format::thousands(visible.len())
+        ))
+        .size(12);
+        layout = layout.push(body);
+        if !self.toasts.is_empty() {
+            layout = layout.push(self.toast_stack());
+        }
+        layout = layout.push(container(status).padding([0, SPACING]));
+        if !self.compare.is_empty() {
+            layout = layout.push(self.compare_view());
+        }
+        if let Some(index) = self.detail {
+            layout = layout.push(self.detail_view(index));
+        }
+        if let Some(index) = self.context_menu {
+            layout = layout.push(self.context_menu_view(index));
+        }
+        if let Some(index) = self.variant_picker {
+            layout = layout.push(self.variant_picker_view(index));
+        }
+        for element in header.into_iter().rev() {
+            layout = layout.push(element);
+        }
+
+        // Wrap the body in a container for background and centering
+        let final_element = self.wrap_background(layout.into());
+
+        // Log duration if debug logging is enabled
+        if let Some(start) = start_time {
+            let duration = start.elapsed();
+            dbug!("View construction took {:?}", duration);
+        }
+
+        final_element
+    }
+
+    fn theme(&self) -> Theme {
+        self.resolve_theme(self.theme_mode)
+    }
+
+    /**
+    Window background behind the content
+    - With rounded corners the window itself has to be clear, or the corners would be filled in
+    @param &self: Self reference
+    @return theme::Application: Default background, or a transparent one
+    */
+    fn style(&self) -> theme::Application {
+        if self.corner_radius > 0.0 {
+            theme::Application::Custom(Box::new(ClearWindow))
+        } else {
+            theme::Application::Default
+        }
+    }
+}
+
+/**
+Helper functions for the application state
+*/
+impl NicePickApp {
+    /**
+    Build the application state from a loaded config and the user data saved on disk
+    @param config: Configuration to start with
+    @param flags: Flags for the application
+    @return (Self, Command<Message>): Initial state and the command to run first
+    */
+    fn with_config(config: Config, flags: StartupFlags) -> (Self, Command<Message>) {
+        // A fresh session starts empty, what's on disk is only kept to save changes on top of
+        let fresh_base = (flags.fresh || config.fresh_start).then(UserData::load);
+        if fresh_base.is_some() {
+            info!("Starting a fresh session, saved recents and favorites aren't restored");
+        }
+        let user_data = if fresh_base.is_some() {
+            UserData::default()
+        } else {
+            UserData::load()
+        };
+        NicePickApp::with_user_data(config, user_data, fresh_base)
+    }
+
+    /**
+    Build the application state from a loaded config and user data, loading the embedded data unless a data file is configured
+    @param config: Configuration to start with
+    @param user_data: Recents, favorites and the rest to start with
+    @param fresh_base: On-disk user data a fresh session leaves out, None for a normal session
+    @return (Self, Command<Message>): Initial state and the command to run first
+    */
+    fn with_user_data(
+        config: Config,
+        user_data: UserData,
+        fresh_base: Option<UserData>,
+    ) -> (Self, Command<Message>) {
+        dbug!("Initializing NicePickApp state (font load deferred until first frame)...");
+
+        // Start in the configured view, unless there's nothing to show there yet
+        let view = match config.start_view {
+            View::Recents | View::Favorites | View::Frequent if config.merge_frequent => {
+                if user_data.recents.is_empty() && user_data.favorites.is_empty() {
+                    View::All
+                } else {
+                    View::Frequent
+                }
+            }
+            View::Frequent => View::Recents, // Not merged, so start in the closest separate view
+            View::Recents if user_data.recents.is_empty() => View::All,
+            View::Favorites if user_data.favorites.is_empty() => View::All,
+            view => view,
+        };
+        dbug!("Starting in view {:?}", view);
+        let layout = user_data.layout.unwrap_or(config.layout);
+        let theme_mode = user_data.theme.unwrap_or(config.theme);
+
+        let mut app = NicePickApp {
+            dataset: Vec::new(),
+            emojis: Vec::new(),
+            load_error: None,
+            data_path: config.data_source(),
+            data_url: config.data_url.clone(),
+            dedup_key: config.dedup_key,
+            loading: None,
+            glyph_index: GlyphIndex::default(),
+            data_generation: 0,
+            visible_cache: RefCell::new(None),
+            emoji_font_loaded: false, // Font is not loaded initially
+            placeholder: config.placeholder,
+            started: Instant::now(),
+            idle_timeout: config.idle_timeout_secs.map(Duration::from_secs),
+            post_copy: config.post_copy,
+            section_sizes: config.section_sizes,
+            section_gap: config.section_gap,
+            close_at: None,
+            unconfirmed: None,
+            last_activity: Instant::now(),
+            rng: Rng::from_time(),
+            sort_mode: config.sort_mode,
+            user_data,
+            fresh_base,
+            unsaved_since: None,
+            scroll_mode: config.scroll_mode,
+            layout,
+            rows_per_page: config.rows_per_page.max(1),
+            columns: column_count(
+                WINDOW_WIDTH,
+                config.min_columns,
+                config.max_columns,
+                cell_width(EMOJI_SIZE, config.cell_shape),
+            ),
+            window_width: WINDOW_WIDTH,
+            min_columns: config.min_columns,
+            max_columns: config.max_columns,
+            page: 0,
+            context_menu: None,
+            categories: Vec::new(),
+            category_filter: config.category_filter,
+            show_hidden_categories: false,
+            newest_version: None,
+            new_only: false,
+            view,
+            scroll_offsets: HashMap::new(),
+            query: String::new(),
+            retain_query: config.retain_query,
+            escape_clears_query: config.escape_clears_query,
+            log_copy_queries: config.log_copy_queries,
+            search_follows_view: config.search_follows_view,
+            min_query_len: config.min_query_len,
+            toasts: Vec::new(),
+            show_help: false,
+            confirm_clears: config.confirm_clears,
+            pending: None,
+            show_log: false,
+            log_filter: String::new(),
+            log_level: Level::Debug,
+            settings: None,
+            font_path: config.font_path,
+            font_requested: false,
+            font_retry_count: 0,
+            font_retry_at: None,
+            font_gave_up: false,
+            search_scope: config.search_scope,
+            search_position: config.search_position,
+            case_sensitive: config.case_sensitive,
+            whole_word_below: config.whole_word_below,
+            synonyms: config.synonyms,
+            feedback: config.feedback,
+            flash: None,
+            clipboard_target: config.clipboard_target,
+            clipboard_stash: ClipboardStash::default(),
+            copy_log: Vec::new(),
+            last_copy: None,
+            copy_as: config.copy_as,
+            selection: Vec::new(),
+            modifiers: keyboard::Modifiers::default(),
+            multi_copy: (
+                config.multi_copy_separator,
+                config.multi_copy_prefix,
+                config.multi_copy_suffix,
+            ),
+            copy_suffix: config.copy_suffix,
+            click_mode: config.click_mode,
+            last_click: None,
+            variant_picker: None,
+            result_limit: RESULT_LIMIT_STEP,
+            merge_frequent: config.merge_frequent,
+            tag_filter: None,
+            tag_draft: String::new(),
+            focus: None,
+            viewport: None,
+            hovered: None,
+            theme_mode,
+            system_appearance: None,
+            hover_color: config.hover_color,
+            emoji_align: config.emoji_align,
+            cell_shape: config.cell_shape,
+            animations: config.animations,
+            filter_changed: None,
+            emoji_offset: config.emoji_offset,
+            corner_radius: config.corner_radius,
+            compare: Vec::new(),
+            detail: None,
+            show_codepoints: config.show_codepoints,
+            show_source: config.show_source,
+            nav_repeat: config.nav_repeat_ms.map(Duration::from_millis),
+            last_nav: None,
+            #[cfg(feature = "animated-preview")]
+            animation_dir: config.animation_dir,
+            #[cfg(feature = "animated-preview")]
+            animation: None,
+        };
+
+        // External data files are loaded in the background with progress, the embedded data right away
+        if app.data_path.is_some() {
+            app.loading = Some((0, 0));
+        } else {
+            // If debug logging is enabled, record the JSON load time
+            let start_time = if logging::log_enabled(Level::Debug) {
+                Some(std::time::Instant::now())
+            } else {
+                None
+            };
+            app.set_emojis(data::load_embedded());
+            if let Some(start) = start_time {
+                dbug!("JSON emoji data loaded in {:?}", start.elapsed());
+            }
+        }
+
+        // The font is loaded after the first frame so the window shows up sooner,
+        // the desktop appearance is asked for straight away so the window doesn't flash the wrong theme
+        let command = if app.theme_mode == ThemeMode::System {
+            Command::perform(async { appearance::detect() }, Message::AppearanceDetected)
+        } else {
+            Command::none()
+        };
+        (app, command)
+    }
+
+    /**
+    Store freshly loaded emoji data and everything derived from it
+    @param &mut self: Mutable self reference
+    @param emojis: Loaded emoji data
+    */
+    fn set_emojis(&mut self, emojis: Vec<EmojiData>) {
+        info!("JSON emoji data loaded successfully");
+        self.dataset = emojis;
+        self.apply_category_filter();
+    }
+
+    /**
+    Rebuild the working emojis from the full dataset, leaving out filtered categories unless they're shown
+    - Everything holding an index into the old emojis is reset
+    @param &mut self: Mutable self reference
+    */
+    fn apply_category_filter(&mut self) {
+        let emojis = if self.show_hidden_categories || self.category_filter.is_empty() {
+            self.dataset.clone()
+        } else {
+            data::filter_categories(&self.dataset, &self.category_filter)
+        };
+
+        // Collect the distinct categories, keeping the order they first appear in
+        let mut categories: Vec<String> = Vec::new();
+        for emoji in &emojis {
+            if !categories.contains(&emoji.category) {
+                categories.push(emoji.category.clone());
+            }
+        }
+        self.glyph_index = GlyphIndex::build(&emojis);
+        self.data_generation += 1;
+        self.newest_version = data::newest_version(&self.dataset);
+        // Without versions the new filter's button is gone, so it can't stay on
+        self.new_only &= self.newest_version.is_some();
+        self.emojis = emojis;
+        self.categories = categories;
+
+        self.focus = None;
+        self.selection.clear();
+        self.hovered = None;
+        self.detail = None;
+        self.context_menu = None;
+        self.variant_picker = None;
+        self.flash = None;
+        self.last_click = None;
+
+        // The view may name a category this dataset doesn't have, or that was just hidden
+        if let View::Category(category) = &self.view {
+            if !self.categories.contains(category) {
+                warn!("Category {:?} not found in the dataset", category);
+                self.view = View::All;
+            }
+        }
+    }
+
+    /**
+    What the user is looking at, for bug reports
+    - Only the search and view state and emoji data, never the clipboard or the copy log
+    @param &self: Self reference
+    @return serde_json::Value: Snapshot, see the debug snapshot shortcut
+    */
+    fn debug_snapshot(&self) -> serde_json::Value {
+        let visible = self.visible_emojis();
+        let results: Vec<serde_json::Value> = visible
+            .iter()
+            .take(SNAPSHOT_RESULTS)
+            .map(|&index| {
+                let emoji = &self.emojis[index];
+                serde_json::json!({
+                    "emoji": emoji.emoji,
+                    "keyword": primary_keyword(emoji),
+                    "category": emoji.category,
+                })
+            })
+            .collect();
+        serde_json::json!({
+            "version": env!("CARGO_PKG_VERSION"),
+            "query": self.query,
+            "view": format!("{:?}", self.view),
+            "tag_filter": self.tag_filter,
+            "new_only": self.new_only,
+            "sort_mode": format!("{:?}", self.sort_mode),
+            "search_scope": format!("{:?}", self.search_scope),
+            "layout": format!("{:?}", self.layout),
+            "scroll_mode": format!("{:?}", self.scroll_mode),
+            "page": self.page,
+            "columns": self.columns,
+            "dataset_size": self.dataset.len(),
+            "emoji_count": self.emojis.len(),
+            "filtered_count": visible.len(),
+            "results": results,
+            "recents": self.user_data.recents,
+            "favorites": self.user_data.favorites,
+        })
+    }
+
+    /**
+    Save the user data, on top of the data a fresh session left unrestored
+    @param &self: Self reference
+    @return std::io::Result<()>: Ok if the data was written
+    */
+    fn save_user_data(&self) -> std::io::Result<()> {
+        match &self.fresh_base {
+            Some(base) => base.merged_with(&self.user_data).save(),
+            None => self.user_data.save(),
+        }
+    }
+
+    /**
+    Show a transient message, dropping the oldest toast if too many are up
+    @param &mut self: Mutable self reference
+    @param text: Message to show
+    @param level: How important the message is
+    */
+    fn push_toast(&mut self, text: String, level: ToastLevel) {
+        if self.toasts.len() >= MAX_TOASTS {
+            self.toasts.remove(0);
+        }
+        self.toasts.push(Toast::new(text, level, Instant::now()));
+    }
+
+    /**
+    Start loading the emoji font in the background
+    - A font file that can't be read isn't retried, glyphs fall back to the system fonts straight away
+    @param &mut self: Mutable self reference
+    @return Command<Message>: Font load, reported back as FontLoaded
+    */
+    fn load_font(&mut self) -> Command<Message> {
+        match emoji_font_bytes(self.font_path.as_deref()) {
+            Ok(bytes) => font::load(bytes).map(Message::FontLoaded),
+            Err(e) => {
+                fail!("Failed to read emoji font: {}", e);
+                self.font_gave_up = true;
+                self.push_toast(
+                    format!("Couldn't read emoji font: {}", e),
+                    ToastLevel::Error,
+                );
+                Command::none()
+            }
+        }
+    }
+
+    /**
+    The search query as far as filtering is concerned
+    @param &self: Self reference
+    @return &str: Trimmed query, empty while it's shorter than min_query_len
+    */
+    fn active_query(&self) -> &str {
+        let query = self.query.trim();
+        if query.chars().count() < self.min_query_len {
+            ""
+        } else {
+            query
+        }
+    }
+
+    /**
+    Indices of the emojis currently shown in the grid, in display order
+    - view() asks for these several times a frame, so the last result is reused until an input changes
+    - Only the indices are memoized, rows and widgets are still built from them every frame
+    @param &self: Self reference
+    @return Vec<usize>: Indices into self.emojis
+    */
+    fn visible_emojis(&self) -> Vec<usize> {
+        let key = VisibleKey {
+            query: self.active_query().to_string(),
+            view: self.view.clone(),
+            tag_filter: self.tag_filter.clone(),
+            new_only: self.new_only,
+            sort_mode: self.sort_mode,
+            search_scope: self.search_scope,
+            case_sensitive: self.case_sensitive,
+            whole_word_below: self.whole_word_below,
+            search_follows_view: self.search_follows_view,
+            data_generation: self.data_generation,
+        };
+        if let Some((cached_key, indices)) = self.visible_cache.borrow().as_ref() {
+            if *cached_key == key {
+                return indices.clone();
+            }
+        }
+
+        let indices = self.compute_visible_emojis();
+        *self.visible_cache.borrow_mut() = Some((key, indices.clone()));
+        indices
+    }
+
+    /**
+    Work out the visible emojis from scratch, filtering, sorting and ranking the search results
+    @param &self: Self reference
+    @return Vec<usize>: Indices into self.emojis, in display order
+    */
+    fn compute_visible_emojis(&self) -> Vec<usize> {
+        let query = self.active_query();
+        let mut indices: Vec<usize> = match &self.view {
+            // Unless searches stay within the open view, they cover everything
+            _ if !query.is_empty() && !self.search_follows_view => (0..self.emojis.len()).collect(),
+            View::All => (0..self.emojis.len()).collect(),
+            View::Recents => self.indices_of(&self.user_data.recents),
+            View::Favorites => self.indices_of(&self.user_data.favorites),
+            View::Frequent => self.indices_of(&self.user_data.frequent()),
+            View::Category(category) => (0..self.emojis.len())
+                .filter(|&i| &self.emojis[i].category == category)
+                .collect(),
+        };
+
+        // Tags narrow down whichever view is active
+        if let Some(tag) = &self.tag_filter {
+            indices.retain(|&i| self.user_data.has_tag(&self.emojis[i].emoji, tag));
+        }
+        if self.new_only {
+            let newest = self.newest_version.as_deref();
+            indices.retain(|&i| self.emojis[i].badge(newest) == Some(Badge::New));
+        }
+
+        // Sorts are stable, so ties keep their dataset order
+        match self.sort_mode {
+            // Recents, favorites and frequent keep their own order
+            _ if matches!(self.view, View::Recents | View::Favorites | View::Frequent) => {}
+            SortMode::Dataset => {}
+            SortMode::Alphabetical => {
+                indices.sort_by_cached_key(|&i| primary_keyword(&self.emojis[i]).to_lowercase());
+            }
+            SortMode::Usage => {
+                indices.sort_by_key(|&i| {
+                    let usage = self.user_data.usage.get(&self.emojis[i].emoji);
+                    std::cmp::Reverse(usage.copied().unwrap_or(0))
+                });
+            }
+        }
+
+        // Search results are ranked by score, falling back to the sort mode for ties
+        if !query.is_empty() {
+            // A pasted emoji finds itself ahead of any text matches
+            let pasted = self.index_of(query);
+            let options = search::MatchOptions {
+                scope: self.search_scope,
+                case_sensitive: self.case_sensitive,
+                whole_word_below: self.whole_word_below,
+            };
+            let mut scored: Vec<(usize, u32)> = indices
+                .into_iter()
+                .filter_map(|i| {
+                    if Some(i) == pasted {
+                        return Some((i, search::GLYPH_SCORE));
+                    }
+                    let emoji = &self.emojis[i];
+                    search::score(query, emoji, &options)
+                        .or_else(|| search::synonym_score(query, emoji, &self.synonyms, &options))
+                        .map(|score| (i, score))
+                })
+                .collect();
+            scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
+            indices = scored.into_iter().map(|(i, _)| i).collect();
+        }
+
+        indices
+    }
+
+    /**
+    Find the index of an emoji by its glyph
+    @param &self: Self reference
+    @param glyph: Emoji glyph
+    @return Option<usize>: Index into self.emojis, variants resolve to their base emoji
+    */
+    fn index_of(&self, glyph: &str) -> Option<usize> {
+        self.glyph_index.position(glyph)
+    }
+
+    /**
+    Resolve a list of glyphs to emoji indices, skipping unknown glyphs and duplicates
+    @param &self: Self reference
+    @param glyphs: Emoji glyphs, e.g. recents or favorites
+    @return Vec<usize>: Indices into self.emojis, in the order of the glyphs
+    */
+    fn indices_of(&self, glyphs: &[impl AsRef<str>]) -> Vec<usize> {
+        let mut indices = Vec::new();
+        for index in glyphs
+            .iter()
+            .filter_map(|glyph| self.index_of(glyph.as_ref()))
+        {
+            // Several variants of one emoji all resolve to the same index
+            if !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+        indices
+    }
+
+    /**
+    Record a copy in the user data, pairing it with the previous copy if that was recent
+    @param &mut self: Mutable self reference
+    @param glyph: The copied glyph
+    */
+    fn record_copy(&mut self, glyph: &str) {
+        self.user_data.record_copy(glyph);
+        if self.log_copy_queries {
+            log_copy_query(glyph, &self.query);
+        }
+        let now = Instant::now();
+        if let Some((previous, at)) = &self.last_copy {
+            if now.duration_since(*at) <= PAIR_WINDOW {
+                self.user_data.record_pair(previous, glyph);
+            }
+        }
+        self.last_copy = Some((glyph.to_string(), now));
+    }
+
+    /**
+    Sections shown above the grid when browsing everything
+    - Suggestions are the emojis most often copied together with the last copy
+    @param &self: Self reference
+    @return [(&str, Vec<&str>, u16); 3]: Title, glyphs and emoji size of each section, glyphs possibly empty
+    */
+    fn browse_sections(&self) -> [(&'static str, Vec<&str>, u16); 3] {
+        let sizes = self.section_sizes;
+        let suggestions = self
+            .user_data
+            .recents
+            .first()
+            .map_or_else(Vec::new, |last| {
+                self.user_data.often_with(last, MAX_SUGGESTIONS)
+            });
+        [
+            (
+                "Favorites",
+                self.user_data
+                    .favorites
+                    .iter()
+                    .map(String::as_str)
+                    .collect(),
+                sizes.favorites.unwrap_or(EMOJI_SIZE),
+            ),
+            (
+                "Recent",
+                self.user_data.recents.iter().map(String::as_str).collect(),
+                sizes.recents.unwrap_or(EMOJI_SIZE),
+            ),
+            (
+                "Often used with",
+                suggestions,
+                sizes.suggestions.unwrap_or(EMOJI_SIZE),
+            ),
+        ]
+    }
+
+    /**
+    Slide the results in after the filter changed, if animations are on
+    - Restarts on every change, typing never waits for a slide to finish
+    @param &mut self: Mutable self reference
+    */
+    fn start_slide(&mut self) {
+        if self.animations {
+            self.filter_changed = Some(Instant::now());
+        }
+    }
+
+    /**
+    Flag user data as changed so it gets saved on a later tick
+    @param &mut self: Mutable self reference
+    */
+    fn mark_unsaved(&mut self) {
+        // Keep the original timestamp so a steady stream of changes still gets saved
+        self.unsaved_since.get_or_insert_with(Instant::now);
+        // Every user data change comes through here, so cached results can't go stale
+        self.data_generation += 1;
+    }
+
+    /**
+    Bring a result into view, turning the page or scrolling as needed
+    @param &mut self: Mutable self reference
+    @param position: Position of the result in the visible emojis
+    @return Command<Message>: Scroll command, if the grid has to move
+    */
+    fn reveal(&mut self, position: usize) -> Command<Message> {
+        if self.scroll_mode == ScrollMode::Paged {
+            self.page = position / self.per_page();
+            return Command::none();
+        }
+
+        // Capped results have to be rendered before they can be scrolled to
+        while position >= self.result_limit {
+            self.result_limit += RESULT_LIMIT_STEP;
+        }
+
+        let row = match self.layout {
+            Layout::Grid => position / self.columns,
+            Layout::List => position,
+        };
+        // The first row also brings the sections above the grid back into view
+        let top = if row == 0 {
+            0.0
+        } else {
+            self.grid_top() + row as f32 * self.row_stride(EMOJI_SIZE)
+        };
+        let bottom = top + row_height(EMOJI_SIZE);
+
+        let offset = self.scroll_offsets.get(&self.view).copied().unwrap_or(0.0);
+        let target = match self.viewport {
+            Some((height, _)) if top >= offset && bottom <= offset + height => {
+                return Command::none();
+            }
+            Some((height, _)) if bottom > offset + height => bottom - height,
+            // Before the first scroll we don't know how tall the grid is, so line the row up at the top
+            _ => top,
+        };
+        let max_offset = self.viewport.map_or(f32::MAX, |(height, content_height)| {
+            (content_height - height).max(0.0)
+        });
+        let target = target.clamp(0.0, max_offset);
+
+        self.scroll_offsets.insert(self.view.clone(), target);
+        scrollable::scroll_to(
+            grid_scroll_id(),
+            scrollable::AbsoluteOffset { x: 0.0, y: target },
+        )
+    }
+
+    /**
+    Distance from the top of the scrollable to the first row of results
+    - Mirrors the layout in view(): padding, then the sections from browse_sections when browsing everything
+    @param &self: Self reference
+    @return f32: Offset of the first result row
+    */
+    fn grid_top(&self) -> f32 {
+        let spacing = f32::from(SPACING);
+        let mut top = spacing;
+        if self.active_query().is_empty() && self.view == View::All {
+            for (_, glyphs, size) in self.browse_sections() {
+                let count = self.indices_of(&glyphs).len();
+                if count == 0 {
+                    continue;
+                }
+                // Each section has its own column count when its size differs from the grid's
+                let rows = match self.layout {
+                    Layout::Grid => count.div_ceil(self.columns_for(size)),
+                    Layout::List => count,
+                };
+                // The last row's spacing is taken by the gap before whatever comes next
+                top += SECTION_TITLE_HEIGHT
+                    + rows as f32 * self.row_stride(size)
+                    + f32::from(self.section_gap);
+            }
+        }
+        top
+    }
+
+    /**
+    Distance from the top of one result row to the next
+    @param &self: Self reference
+    @param size: Emoji font size of the rows
+    @return f32: Row height plus the spacing of the current layout
+    */
+    fn row_stride(&self, size: u16) -> f32 {
+        match self.layout {
+            Layout::Grid => row_height(size) + f32::from(SPACING),
+            Layout::List => row_height(size) + f32::from(SPACING / 2),
+        }
+    }
+
+    /**
+    Number of grid columns for emojis of a size at the current window width
+    @param &self: Self reference
+    @param size: Emoji font size
+    @return usize: Column count within the configured bounds
+    */
+    fn columns_for(&self, size: u16) -> usize {
+        if size == EMOJI_SIZE {
+            return self.columns;
+        }
+        column_count(
+            self.window_width,
+            self.min_columns,
+            self.max_columns,
+            cell_width(size, self.cell_shape),
+        )
+    }
+
+    /**
+    Number of emojis on a page in paged mode
+    @param &self: Self reference
+    @return usize: A page worth of grid rows, or of list rows
+    */
+    fn per_page(&self) -> usize {
+        match self.layout {
+            Layout::Grid => self.columns * self.rows_per_page,
+            Layout::List => self.rows_per_page,
+        }
+    }
+
+    /**
+    Number of pages needed to show a set of emojis in paged mode
+    @param &self: Self reference
+    @param count: Number of emojis to show
+    @return usize: Page count, always at least one
+    */
+    fn page_count(&self, count: usize) -> usize {
+        count.div_ceil(self.per_page()).max(1)
+    }
+
+    /**
+    Wrap content in the full window background container
+    @param &self: Self reference
+    @param content: Content to wrap
+    @return Element<Message>: Centered content on the app background
+    */
+    fn wrap_background<'a>(&self, content: Element<'a, Message>) -> Element<'a, Message> {
+        let radius = self.corner_radius;
+        container(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_x()
+            .center_y()
+            .style(move |theme: &Theme| container::Appearance {
+                background: Some(theme.palette().background.into()),
+                border: Border {
+                    radius: radius.into(),
+                    ..Border::default()
+                },
+                ..container::Appearance::default()
+            })
+            .into()
+    }
+
+    /**
+    Iced theme a theme mode stands for right now
+    @param &self: Self reference
+    @param mode: Theme mode to resolve
+    @return Theme: Light or dark, system mode follows the desktop and falls back to dark
+    */
+    fn resolve_theme(&self, mode: ThemeMode) -> Theme {
+        match mode {
+            ThemeMode::Light => Theme::Light,
+            ThemeMode::Dark => Theme::Dark,
+            ThemeMode::System => match self.system_appearance {
+                Some(Appearance::Light) => Theme::Light,
+                Some(Appearance::Dark) | None => Theme::Dark,
+            },
+        }
+    }
+
+    /**
+    Build the settings overlay, a preview of every theme to pick from
+    - The previews draw with their own palette, the window keeps its theme until Apply
+    @param &self: Self reference
+    @param previewed: Theme currently picked in the overlay
+    @return Element<Message>: Theme previews with apply and cancel buttons
+    */
+    fn settings_view(&self, previewed: ThemeMode) -> Element<Message> {
+        let samples: Vec<&str> = self
+            .emojis
+            .iter()
+            .take(SETTINGS_SAMPLES)
+            .map(|emoji| emoji.emoji.as_str())
+            .collect();
+
+        let mut previews = Row::new().spacing(SPACING);
+        for mode in ThemeMode::ALL {
+            let palette = self.resolve_theme(mode).palette();
+            let picked = mode == previewed;
+
+            let mut cells = Row::new().spacing(SPACING / 2);
+            for &glyph in &samples {
+                let glyph = if self.emoji_font_loaded {
+                    text(glyph).font(EMOJI_FONT).size(EMOJI_SIZE)
+                } else {
+                    text(glyph).size(EMOJI_SIZE)
+                };
+                cells = cells.push(container(glyph).padding(CELL_BORDER).style(
+                    move |_: &Theme| container::Appearance {
+                        border: Border {
+                            color: palette.primary,
+                            width: CELL_BORDER,
+                            radius: 4.0.into(),
+                        },
+                        ..container::Appearance::default()
+                    },
+                ));
+            }
+            let sample = Column::new()
+                .spacing(SPACING / 2)
+                .padding(SPACING)
+                .push(text(mode.label()))
+                .push(cells)
+                .push(text("Search emojis...").size(12));
+
+            // The outline marks the pick, the fill and text come from the previewed palette
+            let preview = container(sample).style(move |theme: &Theme| container::Appearance {
+                background: Some(palette.background.into()),
+                text_color: Some(palette.text),
+                border: Border {
+                    color: if picked {
+                        theme.palette().primary
+                    } else {
+                        Color::TRANSPARENT
+                    },
+                    width: CELL_BORDER,
+                    radius: 4.0.into(),
+                },
+                ..container::Appearance::default()
+            });
+            previews = previews.push(mouse_area(preview).on_press(Message::PreviewTheme(mode)));
+        }
+
+        Column::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .push(text("Theme").size(20))
+            .push(
+                scrollable(previews).direction(scrollable::Direction::Horizontal(
+                    scrollable::Properties::default(),
+                )),
+            )
+            .push(text("Data").size(20))
+            .push(
+                Row::with_children(ClearTarget::ALL.map(|target| {
+                    button(text(format!("Clear {}", target.label())))
+                        .style(theme::Button::Destructive)
+                        .on_press(Message::Clear(target))
+                        .into()
+                }))
+                .spacing(SPACING),
+            )
+            .push(
+                row![
+                    button(text("Apply")).on_press(Message::ApplySettings),
+                    button(text("Cancel"))
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::CancelSettings),
+                ]
+                .spacing(SPACING),
+            )
+            .into()
+    }
+
+    /**
+    Build the keyboard shortcut overlay from the shortcut table
+    @param &self: Self reference
+    @return Element<Message>: Scrollable list of shortcuts
+    */
+    fn help_view(&self) -> Element<Message> {
+        let mut list = Column::new()
+            .spacing(SPACING / 2)
+            .padding(SPACING)
+            .push(text("Keyboard shortcuts").size(20));
+        for bound in shortcuts::keymap().bindings() {
+            list = list.push(row![
+                text(&bound.label).width(Length::Fixed(60.0)),
+                text(bound.action.description()),
+            ]);
+        }
+        list = list.push(text("Press Esc to close").size(12));
+
+        scrollable(list)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+
+    /**
+    Build the log viewer, the kept log lines narrowed down by text and level
+    @param &self: Self reference
+    @return Element<Message>: Filter controls above the matching lines, newest at the bottom
+    */
+    fn log_view(&self) -> Element<Message> {
+        let (lines, total) = logging::history(&self.log_filter, self.log_level);
+
+        let mut controls = Row::new()
+            .spacing(SPACING)
+            .align_items(Alignment::Center)
+            .push(text_input("Filter...", &self.log_filter).on_input(Message::LogFilterChanged));
+        for level in Level::ALL {
+            controls = controls.push(
+                button(text(level.as_str()).size(12))
+                    .style(if level == self.log_level {
+                        theme::Button::Primary
+                    } else {
+                        theme::Button::Secondary
+                    })
+                    .on_press(Message::LogLevelChanged(level)),
+            );
+        }
+        controls = controls
+            .push(
+                button(text("Clear"))
+                    .style(theme::Button::Secondary)
+                    .on_press(Message::ClearLog),
+            )
+            .push(button(text("Close")).on_press(Message::ToggleLog));
+
+        let shown = lines.len();
+        let mut list = Column::new().spacing(2);
+        for line in lines {
+            list = list.push(text(line).font(Font::MONOSPACE).size(12));
+        }
+
+        Column::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .push(text("Log").size(20))
+            .push(controls)
+            .push(
+                text(format!(
+                    "{} of {} lines",
+                    format::thousands(shown),
+                    format::thousands(total)
+                ))
+                .size(12),
+            )
+            .push(scrollable(list).width(Length::Fill).height(Length::Fill))
+            .into()
+    }
+
+    /**
+    Build the search box with the match count, layout toggle and selection copy buttons
+    @param &self: Self reference
+    @param matches: Number of emojis the current search finds
+    @return Element<Message>: Search row
+    */
+    fn search_bar(&self, matches: usize) -> Element<Message> {
+        let input = text_input("Search emojis...", &self.query)
+            .id(search_input_id())
+            .on_input(Message::QueryChanged)
+            .on_submit(Message::SearchSubmit);
+
+        let mut bar = Row::new()
+            .spacing(SPACING)
+            .padding(match self.search_position {
+                SearchPosition::Top => [SPACING, SPACING, 0, SPACING],
+                SearchPosition::Bottom => [0, SPACING, SPACING, SPACING],
+            })
+            .align_items(Alignment::Center)
+            .push(input);
+        // Live feedback on how specific the query is, red once nothing is left
+        let typed = !self.query.trim().is_empty();
+        if typed && self.active_query().is_empty() {
+            bar = bar.push(text("Keep typing…").size(12));
+        } else if typed {
+            let count = text(match matches {
+                1 => "1 match".to_string(),
+                n => format!("{} matches", format::thousands(n)),
+            })
+            .size(12);
+            bar = bar.push(if matches == 0 {
+                count.style(theme::Text::Color(self.theme().palette().danger))
+            } else {
+                count
+            });
+        }
+        let layout_label = match self.layout {
+            Layout::Grid => "List",
+            Layout::List => "Grid",
+        };
+        bar = bar.push(
+            button(text(layout_label))
+                .style(theme::Button::Secondary)
+                .on_press(Message::ToggleLayout),
+        );
+        if !self.selection.is_empty() {
+            bar = bar.push(
+                button(text(format!(
+                    "Copy {}",
+                    format::thousands(self.selection.len())
+                )))
+                .on_press(Message::CopySelection),
+            );
+            // Only worth comparing a few at a time
+            if (2..=MAX_COMPARE).contains(&self.selection.len()) {
+                bar = bar.push(
+                    button(text("Compare"))
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::CompareSelection),
+                );
+            }
+        }
+        bar.into()
+    }
+
+    /**
+    Build the stack of toasts, newest at the bottom
+    @param &self: Self reference
+    @return Element<Message>: Column of colored messages
+    */
+    fn toast_stack(&self) -> Element<Message> {
+        let mut stack = Column::new()
+            .spacing(SPACING / 2)
+            .padding([0, SPACING])
+            .width(Length::Fill)
+            .align_items(Alignment::Center);
+        for toast in &self.toasts {
+            let level = toast.level;
+            stack = stack.push(
+                container(text(&toast.text).size(12))
+                    .padding([SPACING / 2, SPACING])
+                    .style(move |theme: &Theme| container::Appearance {
+                        background: Some(level.color(&theme.palette()).into()),
+                        text_color: Some(theme.palette().background),
+                        border: Border {
+                            radius: 4.0.into(),
+                            ..Border::default()
+                        },
+                        ..container::Appearance::default()
+                    }),
+            );
+        }
+        stack.into()
+    }
+
+    /**
+    Build the row of tag filters, shown once anything has been tagged
+    @param &self: Self reference
+    @return Element<Message>: Row of buttons, the active tag highlighted
+    */
+    fn tag_bar(&self) -> Element<Message> {
+        let tab = |label: &str, tag: Option<String>| {
+            let style = if tag == self.tag_filter {
+                theme::Button::Primary
+            } else {
+                theme::Button::Secondary
+            };
+            button(text(label))
+                .style(style)
+                .on_press(Message::SelectTag(tag))
+        };
+
+        let mut bar = Row::new()
+            .spacing(SPACING)
+            .padding([0, SPACING])
+            .push(tab("Any tag", None));
+        for tag in self.user_data.all_tags() {
+            bar = bar.push(tab(&tag, Some(tag.clone())));
+        }
+        scrollable(bar)
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default(),
+            ))
+            .into()
+    }
+
+    /**
+    Build the row of view tabs, recents and favorites followed by the categories
+    @param &self: Self reference
+    @return Element<Message>: Row of buttons, the active view highlighted
+    */
+    fn category_bar(&self) -> Element<Message> {
+        let tab = |label: &str, view: View| {
+            let style = if view == self.view {
+                theme::Button::Primary
+            } else {
+                theme::Button::Secondary
+            };
+            button(text(label))
+                .style(style)
+                .on_press(Message::SelectView(view))
+        };
+
+        let mut bar = Row::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .push(tab("All", View::All));
+        bar = if self.merge_frequent {
+            bar.push(tab("Frequent", View::Frequent))
+        } else {
+            bar.push(tab("Recent", View::Recents))
+                .push(tab("Favorites", View::Favorites))
+        };
+        for category in &self.categories {
+            bar = bar.push(tab(category, View::Category(category.clone())));
+        }
+        if self.newest_version.is_some() {
+            let style = if self.new_only {
+                theme::Button::Primary
+            } else {
+                theme::Button::Secondary
+            };
+            bar = bar.push(
+                button(text(format!("{} New", Badge::New.marker())))
+                    .style(style)
+                    .on_press(Message::ToggleNewOnly),
+            );
+        }
+        if !self.category_filter.is_empty() {
+            let label = if self.show_hidden_categories {
+                "Hide filtered"
+            } else {
+                "Show all"
+            };
+            bar = bar.push(
+                button(text(label))
+                    .style(theme::Button::Text)
+                    .on_press(Message::ToggleHiddenCategories),
+            );
+        }
+        scrollable(bar)
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Properties::default(),
+            ))
+            .into()
+    }
+
+    /**
+    Build a grid of emoji cells, the last row may be partial
+    @param &self: Self reference
+    @param indices: Indices into self.emojis, in display order
+    @return Element<Message>: Column of emoji rows
+    */
+    fn emoji_rows(&self, indices: &[usize]) -> Element<Message> {
+        let entries = indices
+            .iter()
+            .map(|&index| (index, self.emojis[index].emoji.as_str()))
+            .collect();
+        self.emoji_entries(entries, EMOJI_SIZE)
+    }
+
+    /**
+    Lay out emojis in the current layout
+    @param &self: Self reference
+    @param entries: (index into self.emojis, glyph to show) in display order
+    @param size: Emoji font size, the column count follows from it
+    @return Element<Message>: Grid rows or list rows
+    */
+    fn emoji_entries<'a>(
+        &'a self,
+        entries: Vec<(usize, &'a str)>,
+        size: u16,
+    ) -> Element<'a, Message> {
+        match self.layout {
+            Layout::Grid => {
+                let cells = entries
+                    .into_iter()
+                    .map(|(index, glyph)| self.emoji_cell(index, glyph, size))
+                    .collect();
+                cell_rows(cells, self.columns_for(size))
+            }
+            Layout::List => Column::with_children(
+                entries
+                    .into_iter()
+                    .map(|(index, glyph)| self.list_row(index, glyph, size)),
+            )
+            .spacing(SPACING / 2)
+            .into(),
+        }
+    }
+
+    /**
+    Build a single row of the list layout
+    @param &self: Self reference
+    @param index: Index into self.emojis
+    @param glyph: Glyph to show, the emoji itself or one of its variants
+    @param size: Emoji font size
+    @return Element<Message>: The emoji cell followed by its name and category
+    */
+    fn list_row<'a>(&'a self, index: usize, glyph: &'a str, size: u16) -> Element<'a, Message> {
+        let item = &self.emojis[index];
+        row![
+            self.emoji_cell(index, glyph, size),
+            text(format::truncate(primary_keyword(item), MAX_LABEL_CHARS)).width(Length::Fill),
+            text(format::truncate(&item.category, MAX_LABEL_CHARS)).size(12),
+        ]
+        .spacing(SPACING)
+        .align_items(Alignment::Center)
+        .into()
+    }
+
+    /**
+    Build a single clickable emoji cell
+    @param &self: Self reference
+    @param index: Index into self.emojis
+    @param glyph: Glyph to show, the emoji itself or one of its variants
+    @param size: Emoji font size, the cell grows with it
+    @return Element<Message>: Emoji cell
+    */
+    fn emoji_cell<'a>(&'a self, index: usize, glyph: &'a str, size: u16) -> Element<'a, Message> {
+        let item = &self.emojis[index];
+        let is_base = glyph == item.emoji;
+        // Add each emoji as text with the correct font
+        let emoji_text = if self.emoji_font_loaded {
+            // Use the emoji font if loaded
+            text(glyph).font(EMOJI_FONT).size(size)
+        } else if self.font_gave_up {
+            // The font never loaded, let the system fonts do what they can
+            text(glyph).size(size)
+        } else {
+            // Use a placeholder or default font if not loaded yet
+            self.placeholder_text(size)
+        };
+        // A small marker shows that clicking opens the variant picker instead of copying
+        let mut markers = Vec::new();
+        if is_base && !item.variants.is_empty() {
+            markers.push("▾");
+        }
+        if let Some(badge) = item.badge(self.newest_version.as_deref()) {
+            markers.push(badge.marker());
+        }
+        let content: Element<Message> = if markers.is_empty() {
+            emoji_text.into()
+        } else {
+            row![emoji_text, text(markers.concat()).size(10)]
+                .align_items(Alignment::End)
+                .into()
+        };
+
+        // The copied cell gets a border that fades out over the flash duration
+        let flash_alpha = match self.flash {
+            Some((flashed, started)) if flashed == index => {
+                1.0 - (started.elapsed().as_secs_f32() / FLASH_DURATION.as_secs_f32()).min(1.0)
+            }
+            _ => 0.0,
+        };
+        // Selected cells keep a solid border instead
+        let border_alpha = if self.selection.contains(&index) {
+            1.0
+        } else {
+            flash_alpha
+        };
+
+        // Hover fills the background while the copy flash only touches the border, so both can show at once
+        let hovered = self.hovered == Some(index);
+        let focused = self.focus == Some(index);
+        let hover_color = self.hover_color;
+        // Half the side of a square cell makes the highlight a circle that still fits the glyph
+        let (width, radius) = match self.cell_shape {
+            CellShape::Square => (Length::Shrink, 4.0),
+            CellShape::Circle => (Length::Fixed(row_height(size)), row_height(size) / 2.0),
+        };
+        // The cell keeps its height whatever the alignment, so the highlight and focus ring don't move
+        let framed = container(content)
+            .width(width)
+            .height(Length::Fixed(row_height(size)))
+            .center_x()
+            .align_y(match self.emoji_align {
+                EmojiAlign::Top => alignment::Vertical::Top,
+                EmojiAlign::Center => alignment::Vertical::Center,
+                EmojiAlign::Bottom => alignment::Vertical::Bottom,
+            })
+            .padding(Padding {
+                top: self.emoji_offset.max(0.0),
+                bottom: (-self.emoji_offset).max(0.0),
+                ..Padding::ZERO
+            })
+            .style(move |theme: &Theme| container::Appearance {
+                background: hovered.then(|| hover_background(theme, hover_color).into()),
+                border: Border {
+                    // The focus ring shows whenever the cell isn't flashing or selected
+                    color: if focused && border_alpha == 0.0 {
+                        theme.palette().text
+                    } else {
+                        Color {
+                            a: border_alpha,
+                            ..theme.palette().primary
+                        }
+                    },
+                    width: CELL_BORDER,
+                    radius: radius.into(),
+                },
+                ..container::Appearance::default()
+            });
+
+        // Left click copies (or picks a variant), right click opens the copy-as menu
+        let on_press = if !is_base {
+            Message::CopyVariant(index, glyph.to_string())
+        } else if !item.variants.is_empty() {
+            Message::OpenVariants(index)
+        } else {
+            Message::EmojiClicked(index)
+        };
+        let cell = mouse_area(framed)
+            .on_press(on_press)
+            .on_right_press(Message::OpenContextMenu(index));
+        Hover::new(cell, Message::HoverEnter(index), Message::HoverExit(index)).into()
+    }
+
+    /**
+    Cell content shown while the emoji font is still loading
+    - A single glyph is shown at emoji size, a longer label in small text
+    @param &self: Self reference
+    @param size: Emoji font size of the cell
+    @return Text: Configured placeholder, or the current spinner frame
+    */
+    fn placeholder_text(&self, size: u16) -> Text {
+        match &self.placeholder {
+            Some(label) if label.chars().count() <= 1 => text(label).size(size),
+            Some(label) => {
+                let label: String = label.chars().take(MAX_PLACEHOLDER_CHARS).collect();
+                text(label).size(12)
+            }
+            None => {
+                let frame = self.started.elapsed().as_millis() / SPINNER_INTERVAL.as_millis();
+                text(SPINNER_FRAMES[frame as usize % SPINNER_FRAMES.len()]).size(size)
+            }
+        }
+    }
+
+    /**
+    Build the detail panel for an emoji
+    - Emojis that take a skin tone get a swatch per tone, clicking one copies that tone
+    @param &self: Self reference
+    @param index: Index into self.emojis
+    @return Element<Message>: Large preview with the keywords and category, plus codepoints if enabled
+    */
+    fn detail_view(&self, index: usize) -> Element<Message> {
+        let item = &self.emojis[index];
+
+        // Static glyph, swapped for the animated preview while it's playing
+        let preview: Element<Message> = if self.emoji_font_loaded {
+            text(&item.emoji).font(EMOJI_FONT).size(48).into()
+        } else {
+            text(&item.emoji).size(48).into()
+        };
+        #[cfg(feature = "animated-preview")]
+        let preview = match self.animation.as_ref().filter(|a| a.emoji == item.emoji) {
+            Some(animation) => iced::widget::image(animation.frame())
+                .width(Length::Fixed(48.0))
+                .height(Length::Fixed(48.0))
+                .into(),
+            None => preview,
+        };
+
+        // Every place the search matched is highlighted, in the keywords and the category alike
+        let query = self.active_query();
+        let (keyword_spans, category_spans) = if query.is_empty() {
+            (Vec::new(), Vec::new())
+        } else {
+            let options = search::MatchOptions {
+                scope: self.search_scope,
+                case_sensitive: self.case_sensitive,
+                whole_word_below: self.whole_word_below,
+            };
+            search::match_spans(query, item, &options)
+        };
+        let highlight = self.theme().palette().primary;
+
+        // Full keywords here, wrapped and scrollable in case a dataset entry is huge
+        let mut info = Column::new()
+            .push(
+                container(scrollable(highlighted(
+                    &item.keywords,
+                    &keyword_spans,
+                    16,
+                    highlight,
+                )))
+                .max_height(DETAIL_MAX_HEIGHT),
+            )
+            .width(Length::Fill);
+        // The source shares the category's line, it's rarely worth a line of its own
+        let category = highlighted(&item.category, &category_spans, 12, highlight);
+        info = match item.source.as_ref().filter(|_| self.show_source) {
+            Some(source) => info
+                .push(row![category, text(format!("· {}", source)).size(12)].spacing(SPACING / 2)),
+            None => info.push(category),
+        };
+        if self.show_codepoints {
+            let codepoints = match &item.unicode_version {
+                Some(version) => {
+                    format!("{} · Unicode {}", format::codepoints(&item.emoji), version)
+                }
+                None => format::codepoints(&item.emoji),
+            };
+            info = info.push(text(codepoints).size(12));
+        }
+        if skin_tone::supports_tones(&item.emoji) {
+            let mut swatches = Row::new().spacing(SPACING / 2);
+            for tone in SkinTone::ALL {
+                let toned = skin_tone::apply(&item.emoji, tone);
+                let swatch = if self.emoji_font_loaded {
+                    text(toned).font(EMOJI_FONT).size(20)
+                } else {
+                    text(toned).size(20)
+                };
+                swatches = swatches.push(
+                    button(swatch)
+                        .style(theme::Button::Text)
+                        .padding(2)
+                        .on_press(Message::CopyWithTone(index, tone)),
+                );
+            }
+            info = info.push(swatches);
+        }
+
+        row![preview, info]
+            .spacing(SPACING)
+            .padding(SPACING)
+            .align_items(Alignment::Center)
+            .into()
+    }
+
+    /**
+    Build the compare panel, the pinned emojis side by side at a large size with their names
+    - Clicking a glyph copies it, the x under it takes it out of the panel
+    @param &self: Self reference
+    @return Element<Message>: Row of large glyphs
+    */
+    fn compare_view(&self) -> Element<Message> {
+        let mut panel = Row::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .align_items(Alignment::Start);
+        for glyph in &self.compare {
+            // The dataset can change underneath, e.g. when filtered categories are hidden again
+            let Some(index) = self.index_of(glyph) else {
+                continue;
+            };
+            let label = if self.emoji_font_loaded {
+                text(glyph).font(EMOJI_FONT).size(COMPARE_SIZE)
+            } else {
+                text(glyph).size(COMPARE_SIZE)
+            };
+            let name = format::truncate(primary_keyword(&self.emojis[index]), MAX_LABEL_CHARS);
+            panel = panel.push(
+                Column::new()
+                    .spacing(SPACING / 2)
+                    .align_items(Alignment::Center)
+                    .width(Length::FillPortion(1))
+                    .push(
+                        button(label)
+                            .style(theme::Button::Text)
+                            .on_press(Message::CopyVariant(index, glyph.clone())),
+                    )
+                    .push(text(name).size(12))
+                    .push(
+                        button(text("x"))
+                            .style(theme::Button::Secondary)
+                            .on_press(Message::ToggleCompare(index)),
+                    ),
+            );
+        }
+        panel.into()
+    }
+
+    /**
+    Build the variant picker for an emoji, the base glyph first
+    @param &self: Self reference
+    @param index: Index into self.emojis
+    @return Element<Message>: Row of glyph buttons
+    */
+    fn variant_picker_view(&self, index: usize) -> Element<Message> {
+        let item = &self.emojis[index];
+        let mut picker = Row::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .align_items(Alignment::Center);
+        for glyph in std::iter::once(&item.emoji).chain(&item.variants) {
+            let label = if self.emoji_font_loaded {
+                text(glyph).font(EMOJI_FONT).size(24)
+            } else {
+                text(glyph).size(24)
+            };
+            picker =
+                picker.push(button(label).on_press(Message::CopyVariant(index, glyph.clone())));
+        }
+        picker
+            .push(button(text("x")).on_press(Message::Dismiss))
+            .into()
+    }
+
+    /**
+    Build the copy-as menu for an emoji
+    @param &self: Self reference
+    @param index: Index into self.emojis
+    @return Element<Message>: Row of buttons, one per representation
+    */
+    fn context_menu_view(&self, index: usize) -> Element<Message> {
+        let mut menu = Row::new()
+            .spacing(SPACING)
+            .padding(SPACING)
+            .align_items(Alignment::Center);
+        for representation in Representation::ALL {
+            menu = menu.push(
+                button(text(representation.label()))
+                    .on_press(Message::CopyAs(index, representation)),
+            );
+        }
+        let favorite_label = if self.user_data.favorites.contains(&self.emojis[index].emoji) {
+            "Unfavorite"
+        } else {
+            "Favorite"
+        };
+        menu = menu.push(button(text(favorite_label)).on_press(Message::ToggleFavorite(index)));
+        let compare_label = if self.compare.contains(&self.emojis[index].emoji) {
+            "Uncompare"
+        } else {
+            "Compare"
+        };
+        menu = menu.push(button(text(compare_label)).on_press(Message::ToggleCompare(index)));
+
+        // Existing tags can be clicked away, new ones typed in
+        if let Some(tags) = self.user_data.tags.get(&self.emojis[index].emoji) {
+            let mut tags: Vec<&String> = tags.iter().collect();
+            tags.sort();
+            for tag in tags {
+                menu = menu.push(
+                    button(text(format!("{} x", tag)))
+                        .style(theme::Button::Secondary)
+                        .on_press(Message::RemoveTag(index, tag.clone())),
+                );
+            }
+        }
+        menu.push(
+            text_input("Tag...", &self.tag_draft)
+                .on_input(Message::TagDraftChanged)
+                .on_submit(Message::AddTag(index))
+                .width(Length::Fixed(80.0)),
+        )
+        .push(button(text("x")).on_press(Message::CloseContextMenu))
+        .into()
+    }
+
+    /**
+    Copy every selected emoji in one go, joined as configured, and clear the selection
+    @param &mut self: Mutable self reference
+    @return Command<Message>: Clipboard write command
+    */
+    fn copy_selection(&mut self) -> Command<Message> {
+        let selection = std::mem::take(&mut self.selection);
+        let contents = self.selection_contents(&selection);
+        for &index in &selection {
+            let glyph = self.emojis[index].emoji.clone();
+            self.record_copy(&glyph);
+        }
+        self.mark_unsaved();
+
+        self.log_copy(&contents);
+        info!("Copying {} selected emojis to clipboard", selection.len());
+        if self.feedback.visual() {
+            // A preview of exactly what was copied, separators and format included
+            let preview = format::truncate(&contents, COPY_PREVIEW_CHARS).replace('\n', "↵");
+            self.push_toast(
+                format!(
+                    "Copied {} emojis: {}",
+                    format::thousands(selection.len()),
+                    preview
+                ),
+                ToastLevel::Success,
+            );
+        }
+        self.write_clipboard(contents)
+    }
+
+    /**
+    Text copying a selection puts on the clipboard
+    - The copy suffix goes on once at the very end, after the multi-copy suffix, not after every emoji
+    @param &self: Self reference
+    @param selection: Indices into self.emojis, in the order they were picked
+    @return String: The formatted emojis joined with the multi-copy separator, prefix and suffixes
+    */
+    fn selection_contents(&self, selection: &[usize]) -> String {
+        let items: Vec<String> = selection
+            .iter()
+            .map(|&index| {
+                let item = &self.emojis[index];
+                self.copy_as.format(&item.emoji, primary_keyword(item))
+            })
+            .collect();
+        let (separator, prefix, suffix) = &self.multi_copy;
+        format::join(&items, separator, prefix, suffix) + &self.copy_suffix
+    }
+
+    /**
+    Carry out an action that was held back for confirmation, or didn't need it
+    @param &mut self: Mutable self reference
+    @param pending: Action to run
+    */
+    fn run_pending(&mut self, pending: Pending) {
+        match pending {
+            Pending::Clear(target) => {
+                info!("Clearing {}", target.label());
+                self.user_data.clear(target);
+                if target == ClearTarget::Tags {
+                    self.tag_filter = None;
+                }
+                self.mark_unsaved();
+                self.push_toast(format!("Cleared {}", target.label()), ToastLevel::Info);
+            }
+        }
+    }
+
+    /**
+    Work out what Escape closes next
+    - The topmost thing goes first, help sits above the menu, the window goes last
+    @param &self: Self reference
+    @return Dismissal: What dismissing now would close or clear
+    */
+    fn next_dismissal(&self) -> Dismissal {
+        if self.pending.is_some() {
+            Dismissal::Question
+        } else if self.show_help {
+            Dismissal::Help
+        } else if self.show_log {
+            Dismissal::Log
+        } else if self.settings.is_some() {
+            Dismissal::Settings
+        } else if self.context_menu.is_some() || self.variant_picker.is_some() {
+            Dismissal::Menu
+        } else if !self.compare.is_empty() {
+            Dismissal::Compare
+        } else if !self.selection.is_empty() {
+            Dismissal::Selection
+        } else if self.escape_clears_query && !self.query.is_empty() {
+            Dismissal::Query
+        } else {
+            Dismissal::Window
+        }
+    }
+
+    /**
+    Close the window, saving user data that hasn't been written yet
+    @param &mut self: Mutable self reference
+    @return Command<Message>: Window close
+    */
+    fn close_window(&mut self) -> Command<Message> {
+        if self.unsaved_since.take().is_some() {
+            if let Err(e) = self.save_user_data() {
+                fail!("Failed to save user data: {}", e);
+            }
+        }
+        window::close(window::Id::MAIN)
+    }
+
+    /**
+    Write copied text to the configured clipboard target
+    - The clipboard is read before it's written, keeping what was there for UndoCopy
+    - Each write reports back, see confirm_write()
+    @param &mut self: Mutable self reference
+    @param contents: Text to copy
+    @return Command<Message>: Primary selection write and clipboard read, write and check
+    */
+    fn write_clipboard(&mut self, contents: String) -> Command<Message> {
+        let primary = self.clipboard_target.primary();
+        let clipboard = self.clipboard_target.clipboard();
+        self.unconfirmed = Some(usize::from(primary) + usize::from(clipboard));
+        self.close_at = None;
+
+        let mut commands = Vec::new();
+        if primary {
+            let contents = contents.clone();
+            // The copy tool blocks until it has the text, so keep it off the event loop
+            commands.push(Command::perform(
+                async move {
+                    tokio::task::spawn_blocking(move || system_clipboard::write_primary(&contents))
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()))
+                },
+                Message::PrimaryWritten,
+            ));
+        }
+        if clipboard {
+            // Read what's there first so the copy can be undone, the write follows as ClipboardStashed
+            commands.push(clipboard::read(move |previous| {
+                Message::ClipboardStashed(previous, contents.clone())
+            }));
+        }
+        Command::batch(commands)
+    }
+
+    /**
+    Count a write of the last copy as done, getting the window out of the way once all of them are
+    - A failed write is reported and keeps the window open, whatever post_copy says
+    @param &mut self: Mutable self reference
+    @param target: Name of what was written, for messages
+    @param result: Outcome of the write
+    */
+    fn confirm_write(&mut self, target: &str, result: Result<(), String>) {
+        if let Err(e) = result {
+            fail!("Failed to write the {}: {}", target, e);
+            self.push_toast(format!("Copy to the {} failed", target), ToastLevel::Error);
+            self.unconfirmed = None;
+            self.close_at = None;
+            return;
+        }
+
+        dbug!("Wrote the {}", target);
+        let Some(pending) = self.unconfirmed else {
+            return;
+        };
+        let pending = pending.saturating_sub(1);
+        self.unconfirmed = Some(pending);
+        if pending == 0 {
+            let now = Instant::now();
+            self.close_at = match self.post_copy {
+                PostCopy::Stay => None,
+                PostCopy::Close => Some(now),
+                PostCopy::CloseDelay => Some(now + POST_COPY_DELAY),
+            };
+        }
+    }
+
+    /**
+    Copy an emoji to the clipboard and record the usage
+    @param &mut self: Mutable self reference
+    @param index: Index into self.emojis
+    @param representation: Form the emoji is copied in
+    @return Command<Message>: Clipboard write command
+    */
+    fn copy_emoji(&mut self, index: usize, representation: Representation) -> Command<Message> {
+        let glyph = self.emojis[index].emoji.clone();
+        self.copy_glyph(index, &glyph, representation)
+    }
+
+    /**
+    Copy an emoji in its text or emoji form, for clicks
+    - Only dual-presentation glyphs change, anything else is copied as it is
+    @param &mut self: Mutable self reference
+    @param index: Index into self.emojis
+    @param presentation: Form to copy dual-presentation glyphs in
+    @return Command<Message>: Clipboard write command
+    */
+    fn copy_presentation(&mut self, index: usize, presentation: Presentation) -> Command<Message> {
+        let glyph = presentation::apply(&self.emojis[index].emoji, presentation);
+        self.copy_glyph(index, &glyph, self.copy_as)
+    }
+
+    /**
+    Copy a specific glyph of an emoji to the clipboard and record the usage
+    - Variants are recorded as-is, so they show up in recents as the variant
+    @param &mut self: Mutable self reference
+    @param index: Index into self.emojis
+    @param glyph: The emoji itself or one of its variants
+    @param representation: Form the glyph is copied in
+    @return Command<Message>: Clipboard write command
+    */
+    fn copy_glyph(
+        &mut self,
+        index: usize,
+        glyph: &str,
+        representation: Representation,
+    ) -> Command<Message> {
+        let contents = self.glyph_contents(index, glyph, representation);
+        self.record_copy(glyph);
+        self.mark_unsaved();
+
+        if self.feedback.visual() {
+            self.flash = Some((index, Instant::now()));
+            self.push_toast(format!("Copied {}", contents), ToastLevel::Success);
+        }
+        if self.feedback.sound() {
+            beep();
+        }
+        info!("Copying {} to clipboard", contents);
+        self.log_copy(&contents);
+        self.write_clipboard(contents)
+    }
+
+    /**
+    Text copying a single emoji puts on the clipboard
+    @param &self: Self reference
+    @param index: Index into self.emojis, for the keyword
+    @param glyph: Glyph to copy, the emoji itself or one of its variants
+    @param representation: Form to copy it in
+    @return String: The formatted glyph followed by the copy suffix
+    */
+    fn glyph_contents(&self, index: usize, glyph: &str, representation: Representation) -> String {
+        representation.format(glyph, primary_keyword(&self.emojis[index])) + &self.copy_suffix
+    }
+
+    /**
+    Add a copy to the session's copy log, dropping the oldest entry once it's full
+    @param &mut self: Mutable self reference
+    @param contents: What was copied
+    */
+    fn log_copy(&mut self, contents: &str) {
+        if self.copy_log.len() >= MAX_COPY_LOG {
+            self.copy_log.remove(0);
+        }
+        self.copy_log.push((Instant::now(), contents.to_string()));
+    }
+
+    /**
+    Format the copy log, one line per copy with the time since startup
+    @param &self: Self reference
+    @return String: Lines like "+00:01:23  😀", oldest first
+    */
+    fn format_copy_log(&self) -> String {
+        self.copy_log
+            .iter()
+            .map(|(at, contents)| {
+                let secs = at.duration_since(self.started).as_secs();
+                format!(
+                    "+{:02}:{:02}:{:02}  {}",
+                    secs / 3600,
+                    (secs / 60) % 60,
+                    secs % 60,
+                    contents
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/**
+Window style that leaves everything outside the background container see-through
+*/
+struct ClearWindow;
+
+impl iced::application::StyleSheet for ClearWindow {
+    type Style = Theme;
+
+    fn appearance(&self, style: &Self::Style) -> iced::application::Appearance {
+        iced::application::Appearance {
+            background_color: Color::TRANSPARENT,
+            text_color: style.palette().text,
+        }
+    }
+}
+
+/**
+Background color of a hovered cell
+@param theme: Current theme
+@param configured: Color from the config, if any
+@return Color: Configured color, or a subtle shade from the theme palette
+*/
+fn hover_background(theme: &Theme, configured: Option<HexColor>) -> Color {
+    match configured {
+        Some(HexColor { r, g, b }) => Color::from_rgb8(r, g, b),
+        None => theme.extended_palette().background.weak.color,
+    }
+}
+
+/**
+Log which search query led to a copy, as key=value pairs that are easy to grep for
+- Copies made without a query aren't logged
+@param glyph: The copied glyph
+@param query: Search query active at the time of the copy
+*/
+fn log_copy_query(glyph: &str, query: &str) {
+    if let Some(line) = copy_query_line(glyph, query) {
+        info!("{}", line);
+    }
+}
+
+/**
+Format the log line for a copy made while searching
+@param glyph: The copied glyph
+@param query: Search query active at the time of the copy
+@return Option<String>: The key=value line, None without a query
+*/
+fn copy_query_line(glyph: &str, query: &str) -> Option<String> {
+    let query = query.trim();
+    (!query.is_empty()).then(|| format!("copy emoji={:?} query={:?}", glyph, query))
+}
+
+/**
+Build a yes or no question overlay, answered with ConfirmResult
+@param question: What is being asked
+@return Element<Message>: The question above Yes and No buttons
+*/
+fn confirm_view<'a>(question: &str) -> Element<'a, Message> {
+    Column::new()
+        .spacing(SPACING)
+        .padding(SPACING)
+        .push(text(question.to_string()))
+        .push(
+            row![
+                button(text("Yes"))
+                    .style(theme::Button::Destructive)
+                    .on_press(Message::ConfirmResult(true)),
+                button(text("No"))
+                    .style(theme::Button::Secondary)
+                    .on_press(Message::ConfirmResult(false)),
+            ]
+            .spacing(SPACING),
+        )
+        .into()
+}
+
+/**
+Text with some parts drawn in a highlight color
+@param content: Text to show
+@param spans: Byte ranges to highlight, sorted and disjoint
+@param size: Text size
+@param color: Color of the highlighted parts
+@return Element<Message>: Plain text without spans, otherwise a row of pieces
+*/
+fn highlighted<'a>(
+    content: &'a str,
+    spans: &[(usize, usize)],
+    size: u16,
+    color: Color,
+) -> Element<'a, Message> {
+    if spans.is_empty() {
+        return text(content).size(size).into();
+    }
+    let mut pieces = Row::new();
+    let mut end = 0;
+    for &(start, stop) in spans {
+        if start > end {
+            pieces = pieces.push(text(&content[end..start]).size(size));
+        }
+        pieces = pieces.push(
+            text(&content[start..stop])
+                .size(size)
+                .style(theme::Text::Color(color)),
+        );
+        end = stop;
+    }
+    if end < content.len() {
+        pieces = pieces.push(text(&content[end..]).size(size));
+    }
+    pieces.into()
+}
+
+/**
+Height of a row of emoji cells, the emoji's line height plus the cell border
+@param size: Emoji font size of the row
+@return f32: Row height
+*/
+fn row_height(size: u16) -> f32 {
+    f32::from(size) * LINE_HEIGHT + 2.0 * CELL_BORDER
+}
+
+/**
+Width of an emoji cell
+- Circular cells are square so their highlight is a true circle, the others hug the glyph
+@param size: Emoji font size of the cell
+@param shape: Shape of the cell highlight
+@return f32: Cell width, border included
+*/
+fn cell_width(size: u16, shape: CellShape) -> f32 {
+    match shape {
+        CellShape::Square => f32::from(size) + 2.0 * CELL_BORDER,
+        CellShape::Circle => row_height(size),
+    }
+}
+
+/**
+Number of grid columns that fit a window width
+- The configured bounds win over the fit
+@param width: Window width
+@param min_columns: Fewest columns to use
+@param max_columns: Most columns to use, raised to min_columns if it's lower
+@param cell_width: Width of one cell, see cell_width()
+@return usize: Column count, always at least one
+*/
+fn column_count(width: f32, min_columns: usize, max_columns: usize, cell_width: f32) -> usize {
+    let spacing = f32::from(SPACING);
+    // The grid is padded on both sides, and the last cell has no spacing after it
+    let usable = (width - 2.0 * spacing + spacing).max(0.0);
+    let fit = (usable / (cell_width + spacing)) as usize;
+
+    let min_columns = min_columns.max(1);
+    fit.clamp(min_columns, max_columns.max(min_columns))
+}
+
+/**
+Number of cells in each grid row
+@param count: Number of cells
+@param columns: Cells per row
+@return Vec<usize>: Length of every row, only the last may be short
+*/
+fn row_lengths(count: usize, columns: usize) -> Vec<usize> {
+    let columns = columns.max(1);
+    (0..count.div_ceil(columns))
+        .map(|row| (count - row * columns).min(columns))
+        .collect()
+}
+
+/**
+Lay emoji cells out in rows, the last row may be partial
+@param cells: Cells in display order
+@param columns: Cells per row
+@return Element<Message>: Column of emoji rows
+*/
+fn cell_rows(cells: Vec<Element<Message>>, columns: usize) -> Element<Message> {
+    let lengths = row_lengths(cells.len(), columns);
+    let mut cells = cells.into_iter();
+    let mut rows = Vec::with_capacity(lengths.len());
+    for length in lengths {
+        let row_elements: Row<'_, Message, Theme, Renderer> =
+            Row::with_children(cells.by_ref().take(length)).spacing(SPACING);
+        rows.push(row_elements);
+    }
+
+    // Create a column containing all the rows
+    Column::with_children(rows.into_iter().map(Element::from))
+        .spacing(SPACING)
+        .into()
+}
+
+/**
+Play a short system beep to confirm a copy
+- Uses the terminal bell, so it's only audible when launched from a terminal that rings it
+*/
+#[cfg(feature = "sound")]
+fn beep() {
+    use std::io::Write;
+    let mut stderr = std::io::stderr();
+    let _ = stderr.write_all(b"\x07");
+    let _ = stderr.flush();
+}
+
+/**
+Sound feedback is compiled out without the sound feature
+*/
+#[cfg(not(feature = "sound"))]
+fn beep() {
+    dbug!("Sound feedback requested but nicepick was built without the sound feature");
+}
+
+/**
+Primary keyword of an emoji, the first entry of its comma separated keywords
+@param emoji: Emoji to get the keyword for
+@return &str: Primary keyword
+*/
+fn primary_keyword(emoji: &EmojiData) -> &str {
+    emoji.keywords.split(',').next().unwrap_or("").trim()
+}
+
+/**
+Identifier of the main grid scrollable, used to restore scroll positions
+@return scrollable::Id: Grid scrollable id
+*/
+fn grid_scroll_id() -> scrollable::Id {
+    scrollable::Id::new("emoji-grid")
+}
+
+/**
+Identifier of the search box
+@return text_input::Id: Search input id
+*/
+fn search_input_id() -> text_input::Id {
+    text_input::Id::new("search")
+}
+
+/**
+Get the emoji font bytes, from the configured path or the bundled copy
+@param path: Optional font file path from the config
+@return std::io::Result<Cow<'static, [u8]>>: Font bytes, or an error if no font is available
+*/
+fn emoji_font_bytes(path: Option<&Path>) -> std::io::Result<Cow<'static, [u8]>> {
+    if let Some(path) = path {
+        return std::fs::read(path).map(Cow::Owned);
+    }
+
+    #[cfg(feature = "bundled-font")]
+    {
+        Ok(Cow::Borrowed(NOTO_COLOR_EMOJI_BYTES))
+    }
+
+    #[cfg(not(feature = "bundled-font"))]
+    {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no font_path configured and the bundled font is disabled",
+        ))
+    }
+}
+
+/**
+Time building the window contents with and without the memoized visible emojis, for the --bench mode
+- Recomputed rounds drop the memoized indices first, like every frame did before they were kept
+- Both kinds of round build the full widget tree, memoized rounds only skip filtering and sorting
+- Runs a fresh session, so saved recents and favorites don't skew the numbers
+@return bool: True if the data loaded and every query was timed
+*/
+fn run_bench() -> bool {
+    let (mut app, _) = NicePickApp::new(StartupFlags { fresh: true });
+    // The window loads data files in the background, there's no window here to wait for
+    if app.loading.take().is_some() {
+        match data::load(app.data_path.as_deref(), app.dedup_key, &mut |_, _| {}) {
+            Ok(emojis) => app.set_emojis(emojis),
+            Err(e) => {
+                fail!("Failed to load emoji data: {}", e);
+                return false;
+            }
+        }
+    }
+
+    println!(
+        "{} emojis, average of {} rounds per query",
+        format::thousands(app.emojis.len()),
+        BENCH_ROUNDS
+    );
+    for query in BENCH_QUERIES {
+        app.query = query.to_string();
+        let results = app.visible_emojis().len();
+
+        let start = Instant::now();
+        for _ in 0..BENCH_ROUNDS {
+            app.visible_cache.borrow_mut().take();
+            drop(app.view());
+        }
+        let recomputed = start.elapsed() / BENCH_ROUNDS;
+
+        let start = Instant::now();
+        for _ in 0..BENCH_ROUNDS {
+            drop(app.view());
+        }
+        let memoized = start.elapsed() / BENCH_ROUNDS;
+
+        let speedup = recomputed.as_secs_f64() / memoized.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "{:<8} {:>6} results  {:>10.1?} recomputed  {:>10.1?} memoized  {:.1}x",
+            format!("{:?}", query),
+            format::thousands(results),
+            recomputed,
+            memoized,
+            speedup
+        );
+    }
+    true
+}
+
+/**
+Report how much of the dataset the emoji font can render, for the --check-font mode
+@param list_missing: Also print every emoji the font has no glyphs for
+@return bool: True if the report was printed, missing glyphs don't count as a failure
+*/
+fn report_font_coverage(list_missing: bool) -> bool {
+    let config = Config::load();
+    let emojis = match data::load(
+        config.data_source().as_deref(),
+        config.dedup_key,
+        &mut |_, _| {},
+    ) {
+        Ok(emojis) => emojis,
+        Err(e) => {
+            fail!("Failed to load emoji data: {}", e);
+            return false;
+        }
+    };
+    let font_bytes = match emoji_font_bytes(config.font_path.as_deref()) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            fail!("Failed to read emoji font: {}", e);
+            return false;
+        }
+    };
+    let coverage = match font_check::check(&font_bytes, emojis.iter().map(|e| e.emoji.as_str())) {
+        Ok(coverage) => coverage,
+        Err(e) => {
+            fail!("Failed to parse emoji font: {}", e);
+            return false;
+        }
+    };
+
+    println!(
+        "{} of {} emojis renderable, {} missing",
+        format::thousands(coverage.renderable()),
+        format::thousands(coverage.total),
+        format::thousands(coverage.missing.len())
+    );
+
+    if list_missing {
+        let index = GlyphIndex::build(&emojis);
+        for emoji in &coverage.missing {
+            match index.lookup(&emojis, emoji) {
+                Some(data) => println!("missing: {} ({})", emoji, primary_keyword(data)),
+                None => println!("missing: {}", emoji),
+            }
+        }
+    }
+    true
+}
+
+/**
+Map key presses to application messages
+@param key: The key that was pressed
+@param modifiers: Modifier keys held during the press
+@return Option<Message>: Message to send, if the key is bound to anything
+*/
+fn handle_key_press(key: Key, modifiers: keyboard::Modifiers) -> Option<Message> {
+    let message = match shortcuts::keymap().action_for(key.as_ref(), modifiers)? {
+        Action::RandomPick => Message::RandomPick,
+        Action::CycleSortMode => Message::CycleSortMode,
+        Action::CycleSearchScope => Message::CycleSearchScope,
+        Action::MoveFocus(direction) => Message::MoveFocus(direction),
+        Action::ActivateFocus => Message::ActivateFocus,
+        Action::CopyHovered => Message::CopyHovered,
+        Action::MoveFavorite(direction) => Message::MoveFocusedFavorite(direction),
+        Action::ToggleHelp => Message::ToggleHelp,
+        Action::ToggleLog => Message::ToggleLog,
+        Action::OpenSettings => Message::OpenSettings,
+        Action::ToggleLayout => Message::ToggleLayout,
+        Action::UndoCopy => Message::UndoCopy,
+        Action::ExportCopyLog => Message::ExportCopyLog,
+        Action::DebugSnapshot => Message::DebugSnapshot,
+        Action::Quit => Message::Quit,
+        Action::Dismiss => Message::Dismiss,
+    };
+    Some(message)
+}
+
+/**
+Load an external data file on a background thread, reporting progress as it goes
+- With a data URL the file is fetched first if it isn't cached yet, network errors fall back to the embedded data
+@param path: Data file to load
+@param url: Where to fetch the data file from when it doesn't exist
+@param key: How duplicate entries in the file are recognized
+@return Subscription<Message>: Progress messages, then DataLoaded
+*/
+fn load_data(path: PathBuf, url: Option<String>, key: DedupKey) -> Subscription<Message> {
+    subscription::channel(path.clone(), 64, move |mut output| async move {
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        std::thread::spawn(move || {
+            if let Some(url) = url.filter(|_| !path.exists()) {
+                if let Err(e) = remote::fetch(&url, &path) {
+                    warn!(
+                        "Failed to fetch emoji data from {}: {}, using embedded data",
+                        url, e
+                    );
+                    let _ = sender.send(Message::DataLoaded(Ok(data::load_embedded())));
+                    return;
+                }
+            }
+            let progress_sender = sender.clone();
+            let result = data::load(Some(&path), key, &mut |parsed, total| {
+                let _ = progress_sender.send(Message::DataLoadProgress(parsed, total));
+            });
+            let _ = sender.send(Message::DataLoaded(result.map_err(|e| e.to_string())));
+        });
+
+        while let Some(message) = receiver.recv().await {
+            let _ = output.send(message).await;
+        }
+        // Subscriptions must never finish, this one is dropped once the data is in
+        std::future::pending().await
+    })
+}
+
+/**
+Map window events to messages, only resizes and modifier changes matter to us
+@param event: The window event
+@param _status: Whether a widget already handled the event
+@return Option<Message>: WindowResized for resizes, ModifiersChanged for modifier keys
+*/
+fn handle_window_event(event: Event, _status: event::Status) -> Option<Message> {
+    match event {
+        Event::Window(_, window::Event::Resized { width, .. }) => {
+            Some(Message::WindowResized(width as f32))
+        }
+        Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers)) => {
+            Some(Message::ModifiersChanged(modifiers))
+        }
+        _ => None,
+    }
+}
+
+/**
+Map input events to activity, for the idle timeout
+- Cursor movement is left out, it arrives far too often and hovering already means little
+@param event: The input event
+@param _status: Whether a widget already handled the event
+@return Option<Message>: Activity for key presses, clicks and scrolling
+*/
+fn handle_activity_event(event: Event, _status: event::Status) -> Option<Message> {
+    match event {
+        Event::Keyboard(keyboard::Event::KeyPressed { .. })
+        | Event::Mouse(mouse::Event::ButtonPressed(_) | mouse::Event::WheelScrolled { .. }) => {
+            Some(Message::Activity)
+        }
+        _ => None,
+    }
+}
+
+/**
+Value following a command line flag
+@param args: Command line arguments, without the program name
+@param flag: Flag to look for
+@return Option<&str>: The argument after the flag, None if the flag or its value is missing
+*/
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    let position = args.iter().position(|arg| arg == flag)?;
+    args.get(position + 1).map(String::as_str)
+}
+
+/**
+Where to open the window for an anchor
+- Without a work area only the center can be honored, and only on the window manager's monitor
+@param anchor: Configured anchor, None to leave it to the window manager
+@param work_area: Work area of the monitor with the cursor
+@param size: Window size
+@return window::Position: Position for the window settings
+*/
+fn window_position(
+    anchor: Option<Anchor>,
+    work_area: Option<monitor::Area>,
+    size: Size,
+) -> window::Position {
+    let Some(anchor) = anchor else {
+        return window::Position::Default;
+    };
+    let Some(area) = work_area else {
+        if anchor != Anchor::Center {
+            warn!(
+                "Monitor work area unknown, can't anchor the window to {:?}",
+                anchor
+            );
+        }
+        return window::Position::Centered;
+    };
+
+    let left = area.x;
+    let right = area.x + area.width - size.width;
+    let top = area.y;
+    let bottom = area.y + area.height - size.height;
+    let (x, y) = match anchor {
+        Anchor::TopLeft => (left, top),
+        Anchor::TopRight => (right, top),
+        Anchor::BottomLeft => (left, bottom),
+        Anchor::BottomRight => (right, bottom),
+        Anchor::Center => ((left + right) / 2.0, (top + bottom) / 2.0),
+    };
+    dbug!("Anchoring the window {:?} at {}, {}", anchor, x, y);
+    window::Position::Specific(Point::new(x.max(area.x), y.max(area.y)))
+}
+
+/**
+Check a dataset file for problems, for `nicepick lint <file>`
+- Prints one greppable line per finding and a summary, font coverage findings are warnings
+@param path: Data file to check
+@return bool: True if no errors were found, warnings don't count
+*/
+fn lint_data(path: &Path) -> bool {
+    let file = path.display().to_string();
+    let emojis = match data::load_file(path, &mut |_, _| {}) {
+        Ok(emojis) => emojis,
+        Err(e) => {
+            println!("{}: error: {}", file, e);
+            return false;
+        }
+    };
+
+    let mut known_categories: Vec<String> = Vec::new();
+    for emoji in data::load_embedded() {
+        if !known_categories.contains(&emoji.category) {
+            known_categories.push(emoji.category);
+        }
+    }
+    let mut issues = lint::check(&emojis, &known_categories);
+
+    // Only as good as the configured font, so a missing glyph is a warning
+    let config = Config::load();
+    let coverage = emoji_font_bytes(config.font_path.as_deref())
+        .map_err(|e| e.to_string())
+        .and_then(|bytes| {
+            font_check::check(&bytes, emojis.iter().map(|e| e.emoji.as_str()))
+                .map_err(|e| e.to_string())
+        });
+    match coverage {
+        Ok(coverage) => {
+            let missing: HashSet<&str> = coverage.missing.iter().map(String::as_str).collect();
+            for (position, emoji) in emojis.iter().enumerate() {
+                if missing.contains(emoji.emoji.as_str()) {
+                    issues.push(lint::Issue {
+                        severity: lint::Severity::Warning,
+                        entry: position + 1,
+                        glyph: emoji.emoji.clone(),
+                        message: "not renderable with the configured font".to_string(),
+                    });
+                }
+            }
+        }
+        Err(e) => println!("{}: warning: font coverage not checked: {}", file, e),
+    }
+    issues.sort_by_key(|issue| issue.entry);
+
+    for issue in &issues {
+        println!("{}", issue.report_line(&file));
+    }
+    let errors = issues
+        .iter()
+        .filter(|issue| issue.severity == lint::Severity::Error)
+        .count();
+    println!(
+        "{}: {} entries, {} errors, {} warnings",
+        file,
+        format::thousands(emojis.len()),
+        format::thousands(errors),
+        format::thousands(issues.len() - errors)
+    );
+    errors == 0
+}
+
+/**
+Best match for a query, scored the same way as the search box
+- Ties go to the first emoji in the dataset
+@param query: Trimmed search query
+@param emojis: Emojis to search
+@param config: Scope, case sensitivity and synonyms to search with
+@return Option<&EmojiData>: Best scoring emoji, None if nothing matches
+*/
+fn best_match<'a>(query: &str, emojis: &'a [EmojiData], config: &Config) -> Option<&'a EmojiData> {
+    let options = search::MatchOptions {
+        scope: config.search_scope,
+        case_sensitive: config.case_sensitive,
+        whole_word_below: config.whole_word_below,
+    };
+    let mut best: Option<(&EmojiData, u32)> = None;
+    for emoji in emojis {
+        let score = search::score(query, emoji, &options)
+            .or_else(|| search::synonym_score(query, emoji, &config.synonyms, &options));
+        if let Some(score) = score.filter(|&score| best.is_none_or(|(_, best)| score > best)) {
+            best = Some((emoji, score));
+        }
+    }
+    best.map(|(emoji, _)| emoji)
+}
+
+/**
+Look up queries read line by line from stdin, for `nicepick --stdin`
+- Prints one line per query, the best matching emoji or an empty line for a blank query or no match
+- Nothing is copied or recorded as a use, and it stops at the end of the input
+@return bool: True if all input was read, false if the data or stdin couldn't be read
+*/
+fn stdin_lookup() -> bool {
+    let config = Config::load();
+    let emojis = match data::load(
+        config.data_source().as_deref(),
+        config.dedup_key,
+        &mut |_, _| {},
+    ) {
+        Ok(emojis) => data::filter_categories(&emojis, &config.category_filter),
+        Err(e) => {
+            fail!("Failed to load emoji data: {}", e);
+            return false;
+        }
+    };
+
+    let mut lookups = 0;
+    let mut misses = 0;
+    for line in std::io::stdin().lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(e) => {
+                fail!("Failed to read stdin: {}", e);
+                return false;
+            }
+        };
+        let query = line.trim();
+        if query.is_empty() {
+            println!();
+            continue;
+        }
+        lookups += 1;
+        match best_match(query, &emojis, &config) {
+            Some(emoji) => println!("{}", emoji.emoji),
+            None => {
+                dbug!("No emoji matches {:?}", query);
+                misses += 1;
+                println!();
+            }
+        }
+    }
+    info!(
+        "Looked up {} queries from stdin, {} without a match",
+        format::thousands(lookups),
+        format::thousands(misses)
+    );
+    true
+}
+
+/**
+Copy the best match for a query without opening the window, for `nicepick copy <keyword>`
+- Uses the same scorer, scope and synonyms as the search box, ties go to the first emoji in the dataset
+@param query: Search query
+@return bool: True if something was copied
+*/
+fn quick_copy(query: &str) -> bool {
+    let query = query.trim();
+    if query.is_empty() {
+        eprintln!("Usage: nicepick copy <keyword>");
+        return false;
+    }
+
+    let config = Config::load();
+    let emojis = match data::load(
+        config.data_source().as_deref(),
+        config.dedup_key,
+        &mut |_, _| {},
+    ) {
+        Ok(emojis) => data::filter_categories(&emojis, &config.category_filter),
+        Err(e) => {
+            fail!("Failed to load emoji data: {}", e);
+            return false;
+        }
+    };
+
+    let Some(emoji) = best_match(query, &emojis, &config) else {
+        eprintln!("No emoji matches {:?}", query);
+        return false;
+    };
+
+    let contents =
+        config.copy_as.format(&emoji.emoji, primary_keyword(emoji)) + &config.copy_suffix;
+    if let Err(e) = system_clipboard::write(&contents, system_clipboard::Target::Clipboard) {
+        fail!("Failed to copy {}: {}", contents, e);
+        return false;
+    }
+    println!("{} {}", contents, primary_keyword(emoji));
+
+    // Counts as a use like any other copy
+    if config.log_copy_queries {
+        log_copy_query(&emoji.emoji, query);
+    }
+    let mut user_data = UserData::load();
+    user_data.record_copy(&emoji.emoji);
+    if let Err(e) = user_data.save() {
+        fail!("Failed to save user data: {}", e);
+    }
+    true
+}
+
+/**
+Fetch the dataset from the configured data_url again, replacing the cache if it parses
+@return bool: True if the cache was replaced
+*/
+fn update_data() -> bool {
+    let config = Config::load();
+    let Some(url) = &config.data_url else {
+        fail!("No data_url configured, nothing to update");
+        return false;
+    };
+    let cache = config::data_cache_path();
+    match remote::fetch(url, &cache) {
+        Ok(count) => {
+            okay!("Updated emoji data, {} emojis", format::thousands(count));
+            true
+        }
+        Err(e) => {
+            fail!("Failed to update emoji data from {}: {}", url, e);
+            false
+        }
+    }
+}
+
+/**
+Write the user's tags to a file for sharing alongside a data bundle
+@param path: File to write
+@return bool: True if the tags were written
+*/
+fn export_tags(path: &Path) -> bool {
+    let user_data = UserData::load();
+    match user_data.export_tags(path) {
+        Ok(()) => {
+            okay!("Exported tags to {}", path.display());
+            true
+        }
+        Err(e) => {
+            fail!("Failed to export tags to {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/**
+Write how often and when each emoji was copied to a CSV file, most used first
+- Columns are glyph, primary_keyword, count and last_used, the time being UTC
+- Copies from before last-used times were recorded have an empty last_used
+@param path: File to write
+@return bool: True if the usage was written
+*/
+fn export_usage(path: &Path) -> bool {
+    let config = Config::load();
+    let emojis = match data::load(
+        config.data_source().as_deref(),
+        config.dedup_key,
+        &mut |_, _| {},
+    ) {
+        Ok(emojis) => emojis,
+        Err(e) => {
+            fail!("Failed to load emoji data: {}", e);
+            return false;
+        }
+    };
+    let index = GlyphIndex::build(&emojis);
+    let user_data = UserData::load();
+
+    let mut usage: Vec<(&String, &u32)> = user_data.usage.iter().collect();
+    usage.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    let mut csv = String::from("glyph,primary_keyword,count,last_used\n");
+    for (glyph, count) in usage {
+        let keyword = index.lookup(&emojis, glyph).map_or("", primary_keyword);
+        let last_used = user_data
+            .last_used
+            .get(glyph)
+            .map_or_else(String::new, |&secs| {
+                logging::format_timestamp(&logging::FixedClock(
+                    UNIX_EPOCH + Duration::from_secs(secs),
+                ))
+            });
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            format::csv_field(glyph),
+            format::csv_field(keyword),
+            count,
+            last_used
+        ));
+    }
+
+    match std::fs::write(path, csv) {
+        Ok(()) => {
+            okay!(
+                "Exported usage of {} emojis to {}",
+                user_data.usage.len(),
+                path.display()
+            );
+            true
+        }
+        Err(e) => {
+            fail!("Failed to export usage to {}: {}", path.display(), e);
+            false
+        }
+    }
+}
+
+/**
+Merge tags from a file into the user's own and save them
+@param path: File written by --export-tags
+@return bool: True if the tags were imported and saved
+*/
+fn import_tags(path: &Path) -> bool {
+    let mut user_data = UserData::load();
+    let added = match user_data.import_tags(path) {
+        Ok(added) => added,
+        Err(e) => {
+            fail!("Failed to import tags from {}: {}", path.display(), e);
+            return false;
+        }
+    };
+    match user_data.save() {
+        Ok(()) => {
+            okay!("Imported {} new tags from {}", added, path.display());
+            true
+        }
+        Err(e) => {
+            fail!("Failed to save imported tags: {}", e);
+            false
+        }
+    }
+}
+
+/**
+Main entrypoint of the application
+@returns Iced application
+*/
+fn main() -> iced::Result {
+    let main_start_time = std::time::Instant::now();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // Initialize logging, on stderr unless --log-to names stdout or a file, laid out per --log-format
+    let output = match flag_value(&args, "--log-to") {
+        Some("stdout") => logging::Output::Stdout,
+        Some("stderr") | None => logging::Output::Stderr,
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => logging::Output::Writer(Box::new(std::io::LineWriter::new(file))),
+            Err(e) => {
+                eprintln!("Failed to open log file {}: {}, logging to stderr", path, e);
+                logging::Output::Stderr
+            }
+        },
+    };
+    let template = flag_value(&args, "--log-format").unwrap_or(logging::DEFAULT_TEMPLATE);
+    logging::init(Level::Debug, logging::Limits::default(), output, template);
+    logging::set_module_colors(args.iter().any(|arg| arg == "--log-module-colors"));
+
+    dbug!("Logger initialized in {:?}", main_start_time.elapsed());
+
+    // Make sure crashes end up in the log too
+    logging::install_panic_hook();
+
+    // Diagnostic modes run without ever opening the window
+    if args.iter().any(|arg| arg == "--check-font") {
+        let reported = report_font_coverage(args.iter().any(|arg| arg == "--missing"));
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if reported { 0 } else { 1 });
+    }
+    if args.iter().any(|arg| arg == "--stdin") {
+        let read = stdin_lookup();
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if read { 0 } else { 1 });
+    }
+    if args.iter().any(|arg| arg == "--bench") {
+        let benched = run_bench();
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if benched { 0 } else { 1 });
+    }
+    if args.iter().any(|arg| arg == "--render-test") {
+        let passed = render_test::run(&args);
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+    if args.first().is_some_and(|arg| arg == "lint") {
+        let passed = match args.get(1) {
+            Some(path) => lint_data(Path::new(path)),
+            None => {
+                eprintln!("Usage: nicepick lint <file>");
+                false
+            }
+        };
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if passed { 0 } else { 1 });
+    }
+    if args.first().is_some_and(|arg| arg == "copy") {
+        let copied = quick_copy(&args[1..].join(" "));
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if copied { 0 } else { 1 });
+    }
+    if args.iter().any(|arg| arg == "--update-data") {
+        let updated = update_data();
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if updated { 0 } else { 1 });
+    }
+    if let Some(path) = flag_value(&args, "--export-tags") {
+        let exported = export_tags(Path::new(path));
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if exported { 0 } else { 1 });
+    }
+    if let Some(path) = flag_value(&args, "--export-usage") {
+        let exported = export_usage(Path::new(path));
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if exported { 0 } else { 1 });
+    }
+    if let Some(path) = flag_value(&args, "--import-tags") {
+        let imported = import_tags(Path::new(path));
+        logging::flush(Duration::from_millis(500));
+        std::process::exit(if imported { 0 } else { 1 });
+    }
+
+    info!("Configuring application settings");
+    let flags = StartupFlags {
+        fresh: args.iter().any(|arg| arg == "--fresh"),
+    };
+
+    // Keep the window within the screen, the grid scrolls inside it with the search box pinned
+    let config = Config::load();
+    shortcuts::install(Keymap::new(&config.keybindings));
+    logging::set_scrollback(config.log_scrollback);
+    let work_area = monitor::work_area();
+    let max_height = work_area.map(|area| area.height * config.max_height_share);
+    match max_height {
+        Some(height) => info!("Window height capped at {}px", height),
+        None => dbug!("Monitor work area unknown, window height not capped"),
+    }
+    let size = Size::new(
+        WINDOW_WIDTH,
+        max_height.map_or(WINDOW_HEIGHT, |max| WINDOW_HEIGHT.min(max)),
+    );
+
+    let settings = Settings {
+        window: window::Settings {
+            size,
+            position: window_position(config.anchor, work_area, size),
+            // Never wider than the monitor, unset if the monitor is unknown
+            max_size: work_area
+                .zip(max_height)
+                .map(|(area, height)| Size::new(area.width, height)),
+            decorations: false,
+            transparent: true,
+            ..window::Settings::default()
+        },
+        flags,
+        // Let Iced use its default text font
+        ..Settings::default()
+    };
+
+    let setup_duration = main_start_time.elapsed();
+    dbug!("Application setup (before run) took {:?}", setup_duration);
+    info!("Starting Iced event loop (NicePickApp::run)...");
+
+    let result = NicePickApp::run(settings);
+
+    // Report how the logger coped, then give it a moment to write everything out
+    let stats = logging::stats();
+    dbug!(
+        "Log messages: {} enqueued, {} dropped, {} evicted, {} written, {} kept in memory ({} bytes)",
+        stats.enqueued,
+        stats.dropped,
+        stats.evicted,
+        stats.written,
+        stats.retained,
+        stats.retained_bytes
+    );
+    logging::flush(Duration::from_millis(500));
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Build a dataset entry with just the required fields
+    @param glyph: Emoji glyph
+    @param keywords: Comma separated keywords
+    @param category: Category name
+    @return EmojiData: Entry as if loaded from a data file
+    */
+    fn emoji(glyph: &str, keywords: &str, category: &str) -> EmojiData {
+        serde_json::from_value(serde_json::json!({
+            "emoji": glyph,
+            "keywords": keywords,
+            "category": category,
+        }))
+        .unwrap()
+    }
+
+    /**
+    Start a session with the default config, empty user data and a small dataset
+    - User data only lives in memory, nothing is read from the user's config or data dir
+    @param emojis: Dataset to load
+    @return NicePickApp: Application state
+    */
+    fn test_app(emojis: Vec<EmojiData>) -> NicePickApp {
+        let (mut app, _) =
+            NicePickApp::with_user_data(Config::default(), UserData::default(), None);
+        app.set_emojis(emojis);
+        app
+    }
+
+    /**
+    Glyphs in the grid, in display order
+    @param app: Application state
+    @return Vec<String>: Visible glyphs
+    */
+    fn visible_glyphs(app: &NicePickApp) -> Vec<String> {
+        app.visible_emojis()
+            .into_iter()
+            .map(|index| app.emojis[index].emoji.clone())
+            .collect()
+    }
+
+    /**
+    Each sort mode orders a small dataset as expected, ties keeping dataset order
+    */
+    #[test]
+    fn sort_modes_order_the_grid() {
+        let mut app = test_app(vec![
+            emoji("🍌", "banana, fruit", "Food"),
+            emoji("🍎", "apple, fruit", "Food"),
+            emoji("🥕", "carrot, vegetable", "Food"),
+        ]);
+
+        app.sort_mode = SortMode::Dataset;
+        assert_eq!(visible_glyphs(&app), ["🍌", "🍎", "🥕"]);
+
+        app.sort_mode = SortMode::Alphabetical;
+        assert_eq!(visible_glyphs(&app), ["🍎", "🍌", "🥕"]);
+
+        // Banana and apple are both unused, so they stay in dataset order behind the carrot
+        app.sort_mode = SortMode::Usage;
+        app.user_data.usage.insert("🥕".to_string(), 5);
+        app.mark_unsaved();
+        assert_eq!(visible_glyphs(&app), ["🥕", "🍌", "🍎"]);
+    }
+
+    /**
+    The column count follows the width in between the bounds and is clamped at extreme widths
+    */
+    #[test]
+    fn column_count_clamps_at_extreme_widths() {
+        let cell = 40.0;
+        // 400px wide leaves 390px for cells of 40px plus 10px spacing
+        assert_eq!(column_count(400.0, 1, 20, cell), 7);
+
+        assert_eq!(column_count(0.0, 3, 20, cell), 3);
+        assert_eq!(column_count(-500.0, 3, 20, cell), 3);
+        assert_eq!(column_count(f32::NAN, 3, 20, cell), 3);
+        assert_eq!(column_count(f32::MAX, 3, 20, cell), 20);
+        assert_eq!(column_count(f32::INFINITY, 3, 20, cell), 20);
+
+        // No columns at all is never an option, and a max below the min gives way
+        assert_eq!(column_count(0.0, 0, 0, cell), 1);
+        assert_eq!(column_count(4000.0, 5, 2, cell), 5);
+    }
+
+    /**
+    A 10k character keyword is cut short for display but still matched in full
+    */
+    #[test]
+    fn huge_keyword_is_truncated_but_searchable() {
+        let keywords = format!("{} zebra", "stripes ".repeat(1250));
+        assert!(keywords.chars().count() > 10_000);
+        let mut app = test_app(vec![
+            emoji("🦓", &keywords, "Animals & Nature"),
+            emoji("🐴", "horse", "Animals & Nature"),
+        ]);
+
+        let label = format::truncate(primary_keyword(&app.emojis[0]), MAX_LABEL_CHARS);
+        assert!(label.chars().count() <= MAX_LABEL_CHARS + 1);
+        assert!(label.ends_with('…'));
+
+        let _ = app.update(Message::QueryChanged("zebra".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🦓"]);
+    }
+
+    /**
+    The new filter keeps only emojis from the newest version, seasonal and untagged ones drop out
+    */
+    #[test]
+    fn new_filter_keeps_only_the_newest() {
+        let mut phoenix = emoji("🐦\u{200D}🔥", "phoenix", "Animals & Nature");
+        phoenix.added_version = Some("15.1".to_string());
+        let mut lime = emoji("🍋\u{200D}🟩", "lime", "Food & Drink");
+        lime.added_version = Some("15.1".to_string());
+        let mut moose = emoji("🫎", "moose", "Animals & Nature");
+        moose.added_version = Some("15.0".to_string());
+        let mut tree = emoji("🎄", "christmas tree", "Activities");
+        tree.seasonal = true;
+        let mut app = test_app(vec![
+            phoenix,
+            moose,
+            emoji("😀", "grinning face", "Smileys & Emotion"),
+            tree,
+            lime,
+        ]);
+
+        assert_eq!(visible_glyphs(&app).len(), 5);
+        let _ = app.update(Message::ToggleNewOnly);
+        assert_eq!(visible_glyphs(&app), ["🐦\u{200D}🔥", "🍋\u{200D}🟩"]);
+    }
+
+    /**
+    Moving a favorite swaps it with its neighbour and stops at either end
+    */
+    #[test]
+    fn move_favorite_swaps_and_clamps() {
+        let mut app = test_app(vec![
+            emoji("🍎", "apple", "Food"),
+            emoji("🍌", "banana", "Food"),
+            emoji("🥕", "carrot", "Food"),
+        ]);
+        app.user_data.favorites = ["🍎", "🍌", "🥕"].map(String::from).to_vec();
+
+        let _ = app.update(Message::MoveFavorite(0, Direction::Right));
+        assert_eq!(app.user_data.favorites, ["🍌", "🍎", "🥕"]);
+        let _ = app.update(Message::MoveFavorite(0, Direction::Left));
+        assert_eq!(app.user_data.favorites, ["🍎", "🍌", "🥕"]);
+
+        // Already first, and already last
+        let _ = app.update(Message::MoveFavorite(0, Direction::Up));
+        assert_eq!(app.user_data.favorites, ["🍎", "🍌", "🥕"]);
+        let _ = app.update(Message::MoveFavorite(2, Direction::Down));
+        assert_eq!(app.user_data.favorites, ["🍎", "🍌", "🥕"]);
+
+        let _ = app.update(Message::MoveFavorite(0, Direction::Last));
+        assert_eq!(app.user_data.favorites, ["🍌", "🥕", "🍎"]);
+        let _ = app.update(Message::MoveFavorite(0, Direction::First));
+        assert_eq!(app.user_data.favorites, ["🍎", "🍌", "🥕"]);
+    }
+
+    /**
+    Copies made while searching log the trimmed query, quoted, copies without one log nothing
+    */
+    #[test]
+    fn copy_query_line_quotes_the_query() {
+        assert_eq!(
+            copy_query_line("🍕", "  pizza slice "),
+            Some("copy emoji=\"🍕\" query=\"pizza slice\"".to_string())
+        );
+        assert_eq!(
+            copy_query_line("😀", "say \"hi\""),
+            Some("copy emoji=\"😀\" query=\"say \\\"hi\\\"\"".to_string())
+        );
+        assert_eq!(copy_query_line("😀", ""), None);
+        assert_eq!(copy_query_line("😀", "   "), None);
+    }
+
+    /**
+    Searching in the favorites view stays within the favorites unless search_follows_view is off
+    */
+    #[test]
+    fn favorites_search_follows_the_view() {
+        let mut app = test_app(vec![
+            emoji("🍌", "banana, fruit", "Food"),
+            emoji("🍎", "apple, fruit", "Food"),
+            emoji("🥕", "carrot, vegetable", "Food"),
+        ]);
+        app.sort_mode = SortMode::Dataset;
+        app.user_data.favorites = vec!["🍎".to_string()];
+        app.mark_unsaved();
+        let _ = app.update(Message::SelectView(View::Favorites));
+        let _ = app.update(Message::QueryChanged("fruit".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🍎"]);
+
+        app.search_follows_view = false;
+        assert_eq!(visible_glyphs(&app), ["🍌", "🍎"]);
+
+        // Without a query the view shows all of its own emojis either way
+        let _ = app.update(Message::QueryChanged(String::new()));
+        assert_eq!(visible_glyphs(&app), ["🍎"]);
+    }
+
+    /**
+    Queries shorter than min_query_len leave the grid unfiltered, counting characters after trimming
+    */
+    #[test]
+    fn short_queries_wait_for_min_query_len() {
+        let mut app = test_app(vec![
+            emoji("🍌", "banana, fruit", "Food"),
+            emoji("🍎", "apple, fruit", "Food"),
+            emoji("🥕", "carrot, vegetable", "Food"),
+        ]);
+        app.sort_mode = SortMode::Dataset;
+        app.min_query_len = 3;
+
+        let _ = app.update(Message::QueryChanged("ca".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🍌", "🍎", "🥕"]);
+        let _ = app.update(Message::QueryChanged("  ca  ".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🍌", "🍎", "🥕"]);
+        let _ = app.update(Message::QueryChanged("car".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🥕"]);
+
+        app.min_query_len = 0;
+        let _ = app.update(Message::QueryChanged("c".to_string()));
+        assert_eq!(visible_glyphs(&app), ["🥕"]);
+    }
+
+    /**
+    Dismiss until only the window is left, noting what each Escape press closed
+    - Stops before closing the window, that would save the user data
+    @param app: Application state
+    @return Vec<Dismissal>: Every step in order, ending with Window
+    */
+    fn dismissals(app: &mut NicePickApp) -> Vec<Dismissal> {
+        let mut steps = Vec::new();
+        loop {
+            let next = app.next_dismissal();
+            steps.push(next);
+            if next == Dismissal::Window {
+                return steps;
+            }
+            let _ = app.update(Message::Dismiss);
+        }
+    }
+
+    /**
+    Escape closes the topmost overlay first, then clears the selection and the search, and closes the window last
+    */
+    #[test]
+    fn escape_dismisses_overlays_then_the_query_then_the_window() {
+        let mut app = test_app(vec![
+            emoji("🍌", "banana, fruit", "Food"),
+            emoji("🥕", "carrot, vegetable", "Food"),
+        ]);
+        app.escape_clears_query = true;
+        let _ = app.update(Message::QueryChanged("car".to_string()));
+        app.selection = vec![1];
+        app.compare = vec!["🍌".to_string()];
+        app.show_log = true;
+        app.show_help = true;
+
+        assert_eq!(
+            dismissals(&mut app),
+            [
+                Dismissal::Help,
+                Dismissal::Log,
+                Dismissal::Compare,
+                Dismissal::Selection,
+                Dismissal::Query,
+                Dismissal::Window,
+            ]
+        );
+        assert!(!app.show_help && !app.show_log);
+        assert!(app.compare.is_empty() && app.selection.is_empty());
+        assert_eq!(app.query, "");
+    }
+
+    /**
+    With escape_clears_query off, the search is left alone and the window is next after the selection
+    */
+    #[test]
+    fn escape_skips_the_query_when_clearing_is_off() {
+        let mut app = test_app(vec![emoji("🥕", "carrot, vegetable", "Food")]);
+        app.escape_clears_query = false;
+        let _ = app.update(Message::QueryChanged("car".to_string()));
+        app.selection = vec![0];
+
+        assert_eq!(
+            dismissals(&mut app),
+            [Dismissal::Selection, Dismissal::Window]
+        );
+        assert_eq!(app.query, "car");
+    }
+
+    /**
+    With nothing open and an empty search, the first Escape closes the window either way
+    */
+    #[test]
+    fn escape_closes_the_window_when_nothing_is_open() {
+        let mut app = test_app(vec![emoji("🥕", "carrot, vegetable", "Food")]);
+        for clears in [true, false] {
+            app.escape_clears_query = clears;
+            assert_eq!(dismissals(&mut app), [Dismissal::Window]);
+        }
+    }
+
+    /**
+    Undo puts back what the clipboard held before the latest copy, once
+    */
+    #[test]
+    fn undo_restores_the_latest_stash() {
+        let mut stash = ClipboardStash::default();
+        assert_eq!(stash.undo(), None);
+
+        stash.stash(Some("before".to_string()));
+        assert_eq!(stash.undo().as_deref(), Some("before"));
+        assert_eq!(stash.undo(), None);
+
+        // A second copy replaces the stash, only the latest one can be undone
+        stash.stash(Some("first".to_string()));
+        stash.stash(Some("second".to_string()));
+        assert_eq!(stash.undo().as_deref(), Some("second"));
+        assert_eq!(stash.undo(), None);
+
+        // Copying over an empty clipboard leaves nothing to undo
+        stash.stash(Some("older".to_string()));
+        stash.stash(None);
+        assert_eq!(stash.undo(), None);
+    }
+
+    /**
+    The app stashes the clipboard on a copy and UndoCopy uses it up, with nothing to undo it does nothing
+    */
+    #[test]
+    fn undo_copy_uses_up_the_stash() {
+        let mut app = test_app(vec![emoji("😀", "grinning face", "Smileys")]);
+        let toasts = app.toasts.len();
+        let _ = app.update(Message::UndoCopy);
+        assert_eq!(app.toasts.len(), toasts);
+
+        let _ = app.update(Message::ClipboardStashed(
+            Some("before".to_string()),
+            "😀".to_string(),
+        ));
+        let _ = app.update(Message::UndoCopy);
+        assert_eq!(app.toasts.len(), toasts + 1);
+        assert_eq!(app.clipboard_stash.undo(), None);
+    }
+
+    /**
+    A single copy ends in the copy suffix once, a multi-copy gets it once after the multi-copy suffix
+    */
+    #[test]
+    fn copy_suffix_goes_on_once() {
+        let mut app = test_app(vec![
+            emoji("😀", "grinning face", "Smileys"),
+            emoji("🍕", "pizza", "Food"),
+            emoji("🎉", "party popper", "Activities"),
+        ]);
+        app.copy_as = Representation::Glyph;
+        app.copy_suffix = "!".to_string();
+        app.multi_copy = (",".to_string(), "[".to_string(), "]".to_string());
+
+        assert_eq!(app.glyph_contents(0, "😀", Representation::Glyph), "😀!");
+        assert_eq!(
+            app.glyph_contents(1, "🍕", Representation::Codepoints),
+            "U+1F355!"
+        );
+        assert_eq!(app.selection_contents(&[2, 0, 1]), "[🎉,😀,🍕]!");
+        assert_eq!(app.selection_contents(&[1]), "[🍕]!");
+
+        app.copy_suffix.clear();
+        assert_eq!(app.glyph_contents(0, "😀", Representation::Glyph), "😀");
+        assert_eq!(app.selection_contents(&[0, 1]), "[😀,🍕]");
+    }
 }
@@ -0,0 +1,186 @@
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::dbug;
+#[cfg(all(unix, not(target_os = "macos")))]
+use std::process::Command;
+
+/**
+Rectangle on the virtual screen, in pixels
+*/
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Area {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+impl Area {
+    /**
+    Check if a point lies inside the area
+    @param &self: Self reference
+    @param x: Horizontal position
+    @param y: Vertical position
+    @return bool: True if the point is inside
+    */
+    pub fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /**
+    Overlap of two areas
+    @param &self: Self reference
+    @param other: Area to overlap with
+    @return Option<Area>: The shared part, None if they don't touch
+    */
+    pub fn intersect(&self, other: &Area) -> Option<Area> {
+        let left = self.x.max(other.x);
+        let top = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        (right > left && bottom > top).then_some(Area {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+}
+
+/**
+Work area of the monitor the cursor is on, the part not taken by panels and docks
+- The monitor comes from xrandr and the cursor from xdotool, without xdotool the first monitor is used
+- Panels come from the root window's _NET_WORKAREA through xprop, without it the whole monitor counts
+- X11 only for now, Wayland doesn't tell clients where monitors or panels are
+@return Option<Area>: Work area, None if it couldn't be found
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn work_area() -> Option<Area> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() || std::env::var_os("DISPLAY").is_none() {
+        return None;
+    }
+
+    let panels = root_work_area();
+    let monitors = monitors();
+    let monitor = match cursor() {
+        Some((x, y)) => monitors
+            .iter()
+            .find(|monitor| monitor.contains(x, y))
+            .or(monitors.first()),
+        None => monitors.first(),
+    };
+    match (monitor, panels) {
+        (Some(monitor), Some(panels)) => monitor.intersect(&panels).or(Some(*monitor)),
+        (Some(monitor), None) => Some(*monitor),
+        (None, panels) => panels,
+    }
+}
+
+/**
+Work area of the monitor the cursor is on, not available on this platform
+@return Option<Area>: Always None
+*/
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+pub fn work_area() -> Option<Area> {
+    None
+}
+
+/**
+Run a command and collect what it printed
+@param program: Program to run
+@param args: Its arguments
+@return Option<String>: Standard output, None if it couldn't run or failed
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => {
+            Some(String::from_utf8_lossy(&output.stdout).into_owned())
+        }
+        Ok(output) => {
+            dbug!("{} exited with {}", program, output.status);
+            None
+        }
+        Err(e) => {
+            dbug!("Failed to run {}: {}", program, e);
+            None
+        }
+    }
+}
+
+/**
+Area left over by panels across the whole virtual screen, from _NET_WORKAREA
+@return Option<Area>: Work area, None without xprop or a window manager that sets it
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+fn root_work_area() -> Option<Area> {
+    // "_NET_WORKAREA = x, y, width, height" repeated for each desktop, the first one is enough
+    let output = run("xprop", &["-root", "-notype", "_NET_WORKAREA"])?;
+    let (_, values) = output.split_once('=')?;
+    let values: Vec<f32> = values
+        .split(',')
+        .take(4)
+        .map(|value| value.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let &[x, y, width, height] = values.as_slice() else {
+        return None;
+    };
+    (width > 0.0 && height > 0.0).then_some(Area {
+        x,
+        y,
+        width,
+        height,
+    })
+}
+
+/**
+Geometry of every connected monitor, from xrandr
+@return Vec<Area>: Monitors in xrandr's order, empty without xrandr
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+fn monitors() -> Vec<Area> {
+    let Some(output) = run("xrandr", &["--query"]) else {
+        return Vec::new();
+    };
+    // e.g. "HDMI-1 connected primary 1920x1080+1920+0 (normal left ...) 527mm x 296mm"
+    output
+        .lines()
+        .filter(|line| line.contains(" connected"))
+        .filter_map(|line| line.split_whitespace().find_map(parse_geometry))
+        .collect()
+}
+
+/**
+Parse an X geometry like "1920x1080+1920+0"
+@param geometry: Geometry text
+@return Option<Area>: The area, None if the text isn't a geometry
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+fn parse_geometry(geometry: &str) -> Option<Area> {
+    let (width, rest) = geometry.split_once('x')?;
+    let (height, rest) = rest.split_once('+')?;
+    let (x, y) = rest.split_once('+')?;
+    Some(Area {
+        x: x.parse().ok()?,
+        y: y.parse().ok()?,
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+    })
+}
+
+/**
+Position of the mouse cursor, from xdotool
+@return Option<(f32, f32)>: Cursor position, None without xdotool
+*/
+#[cfg(all(unix, not(target_os = "macos")))]
+fn cursor() -> Option<(f32, f32)> {
+    // "X=123\nY=456\nSCREEN=0\nWINDOW=..."
+    let output = run("xdotool", &["getmouselocation", "--shell"])?;
+    let value = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+            .and_then(|value| value.trim().parse().ok())
+    };
+    Some((value("X")?, value("Y")?))
+}
@@ -0,0 +1,118 @@
+use crate::warn;
+use std::path::PathBuf;
+
+/**
+Name of the directory nicepick keeps its files in, inside the platform's config and data directories
+*/
+const APP_DIR: &str = "nicepick";
+
+/**
+Directory for the config file and other settings, created if it doesn't exist yet
+- Linux: $XDG_CONFIG_HOME/nicepick, or ~/.config/nicepick
+- Windows: %APPDATA%\nicepick
+- macOS: ~/Library/Application Support/nicepick
+- Falls back to the current directory with a warning if none of those can be resolved
+@return PathBuf: Config directory
+*/
+pub fn config_dir() -> PathBuf {
+    resolve("config", platform_config_dir())
+}
+
+/**
+Directory for user data like recents and favorites, created if it doesn't exist yet
+- Linux: $XDG_DATA_HOME/nicepick, or ~/.local/share/nicepick
+- Windows: %APPDATA%\nicepick
+- macOS: ~/Library/Application Support/nicepick
+- Falls back to the current directory with a warning if none of those can be resolved
+@return PathBuf: Data directory
+*/
+pub fn data_dir() -> PathBuf {
+    resolve("data", platform_data_dir())
+}
+
+/**
+Append the app directory to a resolved base and make sure it exists
+@param kind: What the directory is for, for warnings
+@param base: Platform base directory, None if it couldn't be resolved
+@return PathBuf: App directory, or the current directory as a fallback
+*/
+fn resolve(kind: &str, base: Option<PathBuf>) -> PathBuf {
+    let Some(base) = base else {
+        warn!(
+            "Could not determine the {} directory, using the current directory",
+            kind
+        );
+        return PathBuf::from(".");
+    };
+    let dir = base.join(APP_DIR);
+    if let Err(e) = std::fs::create_dir_all(&dir) {
+        warn!("Failed to create {}: {}", dir.display(), e);
+    }
+    dir
+}
+
+/**
+Read an environment variable as a path, ignoring it if unset or empty
+- The XDG spec says relative paths are invalid, so those are ignored too
+@param name: Variable to read
+@return Option<PathBuf>: Absolute path, None if unusable
+*/
+fn env_path(name: &str) -> Option<PathBuf> {
+    let value = std::env::var_os(name).filter(|value| !value.is_empty())?;
+    let path = PathBuf::from(value);
+    path.is_absolute().then_some(path)
+}
+
+/**
+Base config directory on Windows
+@return Option<PathBuf>: %APPDATA%, None if unset
+*/
+#[cfg(windows)]
+fn platform_config_dir() -> Option<PathBuf> {
+    env_path("APPDATA")
+}
+
+/**
+Base data directory on Windows, the same roaming directory as the config
+@return Option<PathBuf>: %APPDATA%, None if unset
+*/
+#[cfg(windows)]
+fn platform_data_dir() -> Option<PathBuf> {
+    env_path("APPDATA")
+}
+
+/**
+Base config directory on macOS
+@return Option<PathBuf>: ~/Library/Application Support, None if HOME is not set
+*/
+#[cfg(target_os = "macos")]
+fn platform_config_dir() -> Option<PathBuf> {
+    env_path("HOME").map(|home| home.join("Library/Application Support"))
+}
+
+/**
+Base data directory on macOS, the same directory as the config
+@return Option<PathBuf>: ~/Library/Application Support, None if HOME is not set
+*/
+#[cfg(target_os = "macos")]
+fn platform_data_dir() -> Option<PathBuf> {
+    platform_config_dir()
+}
+
+/**
+Base config directory on Linux and other unixes
+@return Option<PathBuf>: $XDG_CONFIG_HOME or ~/.config, None if neither can be resolved
+*/
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_config_dir() -> Option<PathBuf> {
+    env_path("XDG_CONFIG_HOME").or_else(|| env_path("HOME").map(|home| home.join(".config")))
+}
+
+/**
+Base data directory on Linux and other unixes
+@return Option<PathBuf>: $XDG_DATA_HOME or ~/.local/share, None if neither can be resolved
+*/
+#[cfg(not(any(windows, target_os = "macos")))]
+fn platform_data_dir() -> Option<PathBuf> {
+    env_path("XDG_DATA_HOME").or_else(|| env_path("HOME").map(|home| home.join(".local/share")))
+}
@@ -0,0 +1,278 @@
+/**
+How a dual-presentation character is drawn, as a colorful emoji or as monochrome text
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presentation {
+    Text,  // Followed by U+FE0E
+    Emoji, // Followed by U+FE0F
+}
+
+/**
+Codepoint ranges of the characters that have both a text and an emoji form
+- The single-character bases from Unicode's emoji-variation-sequences.txt, keycap bases left out
+*/
+const DUAL_PRESENTATION: &[(u32, u32)] = &[
+    (0x00A9, 0x00A9),
+    (0x00AE, 0x00AE),
+    (0x203C, 0x203C),
+    (0x2049, 0x2049),
+    (0x2122, 0x2122),
+    (0x2139, 0x2139),
+    (0x2194, 0x2199),
+    (0x21A9, 0x21AA),
+    (0x231A, 0x231B),
+    (0x2328, 0x2328),
+    (0x23CF, 0x23CF),
+    (0x23E9, 0x23EA),
+    (0x23ED, 0x23EF),
+    (0x23F1, 0x23F3),
+    (0x23F8, 0x23FA),
+    (0x24C2, 0x24C2),
+    (0x25AA, 0x25AB),
+    (0x25B6, 0x25B6),
+    (0x25C0, 0x25C0),
+    (0x25FB, 0x25FE),
+    (0x2600, 0x2604),
+    (0x260E, 0x260E),
+    (0x2611, 0x2611),
+    (0x2614, 0x2615),
+    (0x2618, 0x2618),
+    (0x261D, 0x261D),
+    (0x2620, 0x2620),
+    (0x2622, 0x2623),
+    (0x2626, 0x2626),
+    (0x262A, 0x262A),
+    (0x262E, 0x262F),
+    (0x2638, 0x263A),
+    (0x2640, 0x2640),
+    (0x2642, 0x2642),
+    (0x2648, 0x2653),
+    (0x265F, 0x2660),
+    (0x2663, 0x2663),
+    (0x2665, 0x2666),
+    (0x2668, 0x2668),
+    (0x267B, 0x267B),
+    (0x267E, 0x267F),
+    (0x2692, 0x2697),
+    (0x2699, 0x2699),
+    (0x269B, 0x269C),
+    (0x26A0, 0x26A1),
+    (0x26A7, 0x26A7),
+    (0x26AA, 0x26AB),
+    (0x26B0, 0x26B1),
+    (0x26BD, 0x26BE),
+    (0x26C4, 0x26C5),
+    (0x26C8, 0x26C8),
+    (0x26CE, 0x26CF),
+    (0x26D1, 0x26D1),
+    (0x26D3, 0x26D4),
+    (0x26E9, 0x26EA),
+    (0x26F0, 0x26F5),
+    (0x26F7, 0x26FA),
+    (0x26FD, 0x26FD),
+    (0x2702, 0x2702),
+    (0x2705, 0x2705),
+    (0x2708, 0x270D),
+    (0x270F, 0x270F),
+    (0x2712, 0x2712),
+    (0x2714, 0x2714),
+    (0x2716, 0x2716),
+    (0x271D, 0x271D),
+    (0x2721, 0x2721),
+    (0x2733, 0x2734),
+    (0x2744, 0x2744),
+    (0x2747, 0x2747),
+    (0x2753, 0x2753),
+    (0x2757, 0x2757),
+    (0x2763, 0x2764),
+    (0x27A1, 0x27A1),
+    (0x2934, 0x2935),
+    (0x2B05, 0x2B07),
+    (0x2B1B, 0x2B1C),
+    (0x2B50, 0x2B50),
+    (0x2B55, 0x2B55),
+    (0x3030, 0x3030),
+    (0x303D, 0x303D),
+    (0x3297, 0x3297),
+    (0x3299, 0x3299),
+    (0x1F004, 0x1F004),
+    (0x1F170, 0x1F171),
+    (0x1F17E, 0x1F17F),
+    (0x1F202, 0x1F202),
+    (0x1F21A, 0x1F21A),
+    (0x1F22F, 0x1F22F),
+    (0x1F237, 0x1F237),
+    (0x1F30D, 0x1F30F),
+    (0x1F315, 0x1F315),
+    (0x1F31C, 0x1F31C),
+    (0x1F321, 0x1F321),
+    (0x1F324, 0x1F32C),
+    (0x1F336, 0x1F336),
+    (0x1F378, 0x1F378),
+    (0x1F37D, 0x1F37D),
+    (0x1F393, 0x1F393),
+    (0x1F396, 0x1F397),
+    (0x1F399, 0x1F39B),
+    (0x1F39E, 0x1F39F),
+    (0x1F3A7, 0x1F3A7),
+    (0x1F3AC, 0x1F3AE),
+    (0x1F3C2, 0x1F3C2),
+    (0x1F3C4, 0x1F3C4),
+    (0x1F3C6, 0x1F3C6),
+    (0x1F3CA, 0x1F3CE),
+    (0x1F3D4, 0x1F3E0),
+    (0x1F3ED, 0x1F3ED),
+    (0x1F3F3, 0x1F3F3),
+    (0x1F3F5, 0x1F3F5),
+    (0x1F3F7, 0x1F3F7),
+    (0x1F408, 0x1F408),
+    (0x1F415, 0x1F415),
+    (0x1F41F, 0x1F41F),
+    (0x1F426, 0x1F426),
+    (0x1F43F, 0x1F43F),
+    (0x1F441, 0x1F442),
+    (0x1F446, 0x1F449),
+    (0x1F44D, 0x1F44E),
+    (0x1F453, 0x1F453),
+    (0x1F46A, 0x1F46A),
+    (0x1F47D, 0x1F47D),
+    (0x1F4A3, 0x1F4A3),
+    (0x1F4B0, 0x1F4B0),
+    (0x1F4B3, 0x1F4B3),
+    (0x1F4BB, 0x1F4BB),
+    (0x1F4BF, 0x1F4BF),
+    (0x1F4CB, 0x1F4CB),
+    (0x1F4DA, 0x1F4DA),
+    (0x1F4DF, 0x1F4DF),
+    (0x1F4E4, 0x1F4E6),
+    (0x1F4EA, 0x1F4ED),
+    (0x1F4F7, 0x1F4F7),
+    (0x1F4F9, 0x1F4FB),
+    (0x1F4FD, 0x1F4FD),
+    (0x1F508, 0x1F508),
+    (0x1F50D, 0x1F50D),
+    (0x1F512, 0x1F513),
+    (0x1F549, 0x1F54A),
+    (0x1F550, 0x1F567),
+    (0x1F56F, 0x1F570),
+    (0x1F573, 0x1F579),
+    (0x1F587, 0x1F587),
+    (0x1F58A, 0x1F58D),
+    (0x1F590, 0x1F590),
+    (0x1F5A5, 0x1F5A5),
+    (0x1F5A8, 0x1F5A8),
+    (0x1F5B1, 0x1F5B2),
+    (0x1F5BC, 0x1F5BC),
+    (0x1F5C2, 0x1F5C4),
+    (0x1F5D1, 0x1F5D3),
+    (0x1F5DC, 0x1F5DE),
+    (0x1F5E1, 0x1F5E1),
+    (0x1F5E3, 0x1F5E3),
+    (0x1F5E8, 0x1F5E8),
+    (0x1F5EF, 0x1F5EF),
+    (0x1F5F3, 0x1F5F3),
+    (0x1F5FA, 0x1F5FA),
+    (0x1F610, 0x1F610),
+    (0x1F687, 0x1F687),
+    (0x1F68D, 0x1F68D),
+    (0x1F691, 0x1F691),
+    (0x1F694, 0x1F694),
+    (0x1F698, 0x1F698),
+    (0x1F6AD, 0x1F6AD),
+    (0x1F6B2, 0x1F6B2),
+    (0x1F6B9, 0x1F6BA),
+    (0x1F6BC, 0x1F6BC),
+    (0x1F6CB, 0x1F6CB),
+    (0x1F6CD, 0x1F6CF),
+    (0x1F6E0, 0x1F6E5),
+    (0x1F6E9, 0x1F6E9),
+    (0x1F6F0, 0x1F6F0),
+    (0x1F6F3, 0x1F6F3),
+];
+
+impl Presentation {
+    /**
+    Variation selector asking for this presentation
+    @param self: Presentation
+    @return char: U+FE0E or U+FE0F
+    */
+    pub fn selector(self) -> char {
+        match self {
+            Presentation::Text => '\u{FE0E}',
+            Presentation::Emoji => '\u{FE0F}',
+        }
+    }
+}
+
+/**
+Find the single character of a glyph, ignoring any presentation selector it already has
+@param glyph: Emoji glyph
+@return Option<char>: The character, None for sequences of several characters
+*/
+fn single_char(glyph: &str) -> Option<char> {
+    let mut chars = glyph
+        .chars()
+        .filter(|&c| c != '\u{FE0E}' && c != '\u{FE0F}');
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+/**
+Check if a glyph can be shown both as text and as an emoji
+@param glyph: Emoji glyph, with or without a presentation selector
+@return bool: True for single dual-presentation characters like ❤ or ☺
+*/
+pub fn is_dual(glyph: &str) -> bool {
+    single_char(glyph).is_some_and(|c| {
+        let c = c as u32;
+        DUAL_PRESENTATION
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&c))
+    })
+}
+
+/**
+Ask for a presentation of a glyph, replacing any selector it already has
+- Glyphs without a text and emoji form are returned as they are
+@param glyph: Emoji glyph
+@param presentation: Form to ask for
+@return String: The character followed by the selector for the presentation
+*/
+pub fn apply(glyph: &str, presentation: Presentation) -> String {
+    match single_char(glyph) {
+        Some(c) if is_dual(glyph) => [c, presentation.selector()].iter().collect(),
+        _ => glyph.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    A dual-presentation heart takes either selector, replacing the one it had
+    */
+    #[test]
+    fn apply_gives_both_forms_of_a_dual_glyph() {
+        assert!(is_dual("❤"));
+        assert_eq!(apply("❤", Presentation::Text), "❤\u{FE0E}");
+        assert_eq!(apply("❤", Presentation::Emoji), "❤\u{FE0F}");
+        assert_eq!(apply("❤\u{FE0F}", Presentation::Text), "❤\u{FE0E}");
+        assert_eq!(apply("❤\u{FE0E}", Presentation::Emoji), "❤\u{FE0F}");
+    }
+
+    /**
+    Glyphs with a single form and sequences come back unchanged
+    */
+    #[test]
+    fn apply_leaves_other_glyphs_alone() {
+        assert!(!is_dual("😀"));
+        assert_eq!(apply("😀", Presentation::Text), "😀");
+        assert_eq!(apply("😀", Presentation::Emoji), "😀");
+        assert!(!is_dual("❤\u{200D}🔥"));
+        assert_eq!(
+            apply("❤\u{FE0F}\u{200D}🔥", Presentation::Text),
+            "❤\u{FE0F}\u{200D}🔥"
+        );
+    }
+}
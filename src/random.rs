@@ -0,0 +1,147 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/**
+Tiny xorshift64* generator
+- Good enough for picking a random emoji, not for anything that needs real randomness
+*/
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /**
+    Create a generator seeded from the current time, so every run gets a different sequence
+    @return Rng: Newly seeded generator
+    */
+    pub fn from_time() -> Self {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        // Xorshift gets stuck on a zero state, so make sure we never start there
+        Rng { state: nanos | 1 }
+    }
+
+    /**
+    Create a generator with a fixed seed, for tests that need a known sequence
+    @param seed: Any value, zero included
+    @return Rng: Seeded generator
+    */
+    #[cfg(test)]
+    pub fn from_seed(seed: u64) -> Self {
+        // Spread the seed out so nearby seeds don't start out with similar sequences
+        let state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        Rng {
+            state: if state == 0 { 1 } else { state },
+        }
+    }
+
+    /**
+    Advance the generator
+    @param &mut self: Mutable self reference
+    @return u64: Next pseudo-random value
+    */
+    pub fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x >> 12;
+        x ^= x << 25;
+        x ^= x >> 27;
+        self.state = x;
+        x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /**
+    Pick an index uniformly from 0..len
+    @param &mut self: Mutable self reference
+    @param len: Number of candidates, must be greater than zero
+    @return usize: Index in the range 0..len
+    */
+    pub fn index(&mut self, len: usize) -> usize {
+        let len = len as u64;
+        // Reject values from the uneven tail so every index is equally likely
+        let zone = u64::MAX - (u64::MAX % len);
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return (value % len) as usize;
+            }
+        }
+    }
+
+    /**
+    Pick one of the candidates uniformly
+    @param &mut self: Mutable self reference
+    @param candidates: Items to choose from
+    @return Option<T>: The picked item, None if there are no candidates
+    */
+    pub fn pick<T: Copy>(&mut self, candidates: &[T]) -> Option<T> {
+        if candidates.is_empty() {
+            return None;
+        }
+        Some(candidates[self.index(candidates.len())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Picks from a sparse candidate set never land outside it, whatever the seed
+    */
+    #[test]
+    fn pick_stays_within_candidates() {
+        let candidates = [3usize, 17, 42, 99];
+        for seed in 0..200 {
+            let mut rng = Rng::from_seed(seed);
+            for _ in 0..50 {
+                let picked = rng.pick(&candidates).unwrap();
+                assert!(
+                    candidates.contains(&picked),
+                    "{} is not a candidate",
+                    picked
+                );
+            }
+        }
+    }
+
+    /**
+    A single candidate is always the pick, and no candidates means no pick
+    */
+    #[test]
+    fn pick_handles_tiny_candidate_sets() {
+        let mut rng = Rng::from_time();
+        assert_eq!(rng.pick(&[7usize]), Some(7));
+        assert_eq!(rng.pick::<usize>(&[]), None);
+    }
+
+    /**
+    Indices cover the whole range and nothing past it
+    */
+    #[test]
+    fn index_stays_in_range() {
+        let mut rng = Rng::from_seed(1);
+        let mut seen = [false; 5];
+        for _ in 0..1000 {
+            let index = rng.index(5);
+            assert!(index < 5);
+            seen[index] = true;
+        }
+        assert!(
+            seen.iter().all(|&hit| hit),
+            "some index never came up: {:?}",
+            seen
+        );
+    }
+
+    /**
+    The same seed always gives the same picks
+    */
+    #[test]
+    fn seeded_picks_are_reproducible() {
+        let candidates = ["😀", "🎉", "🍕", "❤️", "👍"];
+        let mut rng = Rng::from_seed(42);
+        let picks: Vec<&str> = (0..6).map(|_| rng.pick(&candidates).unwrap()).collect();
+        assert_eq!(picks, ["😀", "👍", "😀", "❤️", "❤️", "😀"]);
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(feature = "remote-data")]
+use crate::{data, dbug, info};
+use std::path::Path;
+
+/**
+Download a dataset and cache it, replacing the cached copy only once the download parses
+- The download goes to a temporary file next to the cache first, so a failed write never leaves half a dataset behind
+@param url: Where to fetch the dataset from
+@param cache: Path of the cached dataset
+@return Result<usize, String>: Number of emojis in the new dataset, or why it couldn't be fetched
+*/
+#[cfg(feature = "remote-data")]
+pub fn fetch(url: &str, cache: &Path) -> Result<usize, String> {
+    info!("Fetching emoji data from {}", url);
+    let contents = ureq::get(url)
+        .call()
+        .map_err(|e| e.to_string())?
+        .into_string()
+        .map_err(|e| e.to_string())?;
+    dbug!("Downloaded {} bytes of emoji data", contents.len());
+
+    let count = data::parse_json(&contents)
+        .map_err(|e| e.to_string())?
+        .len();
+
+    if let Some(dir) = cache.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let partial = cache.with_extension("json.part");
+    std::fs::write(&partial, &contents).map_err(|e| e.to_string())?;
+    std::fs::rename(&partial, cache).map_err(|e| e.to_string())?;
+    info!("Cached {} emojis at {}", count, cache.display());
+    Ok(count)
+}
+
+/**
+Fetching needs the remote-data feature
+@param _url: Where the dataset would have come from
+@param _cache: Where it would have been cached
+@return Result<usize, String>: Always an error explaining the missing feature
+*/
+#[cfg(not(feature = "remote-data"))]
+pub fn fetch(_url: &str, _cache: &Path) -> Result<usize, String> {
+    Err("nicepick was built without the remote-data feature".to_string())
+}
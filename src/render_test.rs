@@ -0,0 +1,332 @@
+use super::{
+    CellShape, Config, EMOJI_SIZE, Layout, Message, NicePickApp, SPACING, UserData, WINDOW_WIDTH,
+    cell_width, column_count, data, flag_value,
+};
+use iced::Application;
+
+/**
+One window size and column setting with the grid it should produce
+*/
+struct Case {
+    width: f32,
+    min_columns: usize,
+    max_columns: usize,
+    cell_shape: CellShape,
+    emojis: usize,
+    columns: usize, // Expected cells per row
+    rows: usize,    // Expected number of rows
+}
+
+/**
+Known layouts, covering the clamps at both ends and partial last rows
+*/
+const CASES: &[Case] = &[
+    // Default window and settings
+    Case {
+        width: 400.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 100,
+        columns: 8,
+        rows: 13,
+    },
+    Case {
+        width: 200.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 10,
+        columns: 4,
+        rows: 3,
+    },
+    // Too narrow for a single cell, min_columns still applies
+    Case {
+        width: 40.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 5,
+        columns: 1,
+        rows: 5,
+    },
+    Case {
+        width: 400.0,
+        min_columns: 10,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 25,
+        columns: 10,
+        rows: 3,
+    },
+    // Wide windows stop at max_columns
+    Case {
+        width: 1000.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 16,
+        columns: 16,
+        rows: 1,
+    },
+    Case {
+        width: 400.0,
+        min_columns: 1,
+        max_columns: 4,
+        cell_shape: CellShape::Square,
+        emojis: 9,
+        columns: 4,
+        rows: 3,
+    },
+    // A max below the min is treated as the min
+    Case {
+        width: 400.0,
+        min_columns: 6,
+        max_columns: 2,
+        cell_shape: CellShape::Square,
+        emojis: 12,
+        columns: 6,
+        rows: 2,
+    },
+    // Circular cells are as wide as they are tall, so fewer fit
+    Case {
+        width: 400.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Circle,
+        emojis: 20,
+        columns: 7,
+        rows: 3,
+    },
+    Case {
+        width: 400.0,
+        min_columns: 1,
+        max_columns: 16,
+        cell_shape: CellShape::Square,
+        emojis: 0,
+        columns: 8,
+        rows: 0,
+    },
+];
+
+/**
+Width the grid takes up with a number of columns, padding included
+@param columns: Cells per row
+@param cell_width: Width of one cell, see cell_width()
+@return f32: Width in logical pixels
+*/
+fn grid_width(columns: usize, cell_width: f32) -> f32 {
+    let spacing = f32::from(SPACING);
+    2.0 * spacing + columns as f32 * (cell_width + spacing) - spacing
+}
+
+/**
+Most columns of a cell width that fit a window, clamped like the settings ask
+- Worked out from grid_width() instead of column_count(), so the two keep each other honest
+@param width: Window width
+@param min_columns: Fewest columns to use
+@param max_columns: Most columns to use
+@param cell_width: Width of one cell
+@return usize: Expected column count
+*/
+fn expected_columns(width: f32, min_columns: usize, max_columns: usize, cell_width: f32) -> usize {
+    let min_columns = min_columns.max(1);
+    let max_columns = max_columns.max(min_columns);
+    (min_columns..=max_columns)
+        .rev()
+        .find(|&columns| grid_width(columns, cell_width) <= width)
+        .unwrap_or(min_columns)
+}
+
+/**
+Start the app on a known dataset, resize it and count the rows and cells its grid builds
+@param case: Window size, column settings and number of emojis
+@return (usize, Vec<usize>): Columns the app picked, and the number of cells in each grid row
+*/
+fn render(case: &Case) -> (usize, Vec<usize>) {
+    let config = Config {
+        min_columns: case.min_columns,
+        max_columns: case.max_columns,
+        cell_shape: case.cell_shape,
+        ..Config::default()
+    };
+    let (mut app, _) = NicePickApp::with_user_data(config, UserData::default(), None);
+    let emojis = data::load_embedded()
+        .into_iter()
+        .cycle()
+        .take(case.emojis)
+        .collect();
+    app.set_emojis(emojis);
+    app.layout = Layout::Grid;
+
+    let _ = app.update(Message::WindowResized(case.width));
+    let grid = app.emoji_rows(&app.visible_emojis());
+    // The grid is a column of rows, each row holding one child per cell
+    let rows = grid
+        .as_widget()
+        .children()
+        .iter()
+        .map(|row| row.children.len())
+        .collect();
+    (app.columns, rows)
+}
+
+/**
+Check one case against the grid the app builds for it
+@param case: Case to check
+@return Vec<String>: What didn't match, empty if the case passed
+*/
+fn check(case: &Case) -> Vec<String> {
+    let mut problems = Vec::new();
+    let cell_width = cell_width(EMOJI_SIZE, case.cell_shape);
+    let expected = expected_columns(case.width, case.min_columns, case.max_columns, cell_width);
+    if expected != case.columns {
+        problems.push(format!(
+            "{} columns fit, the case expects {}",
+            expected, case.columns
+        ));
+    }
+    let counted = column_count(case.width, case.min_columns, case.max_columns, cell_width);
+    if counted != case.columns {
+        problems.push(format!(
+            "column_count gives {}, expected {}",
+            counted, case.columns
+        ));
+    }
+
+    let (columns, rows) = render(case);
+    if columns != case.columns {
+        problems.push(format!("{} columns, expected {}", columns, case.columns));
+    }
+    if rows.len() != case.rows {
+        problems.push(format!("{} rows, expected {}", rows.len(), case.rows));
+    }
+    if rows.iter().sum::<usize>() != case.emojis {
+        problems.push(format!(
+            "{} cells, expected {}",
+            rows.iter().sum::<usize>(),
+            case.emojis
+        ));
+    }
+    // Every row is full except maybe the last
+    let (last, full) = rows.split_last().unwrap_or((&0, &[]));
+    if full.iter().any(|&len| len != case.columns) || *last > case.columns {
+        problems.push(format!(
+            "row lengths {:?} don't fit {} columns",
+            rows, case.columns
+        ));
+    }
+    // Only the min_columns clamp may push the grid past the window edge
+    if columns > case.min_columns && grid_width(columns, cell_width) > case.width {
+        problems.push(format!(
+            "grid is {} wide, window is {}",
+            grid_width(columns, cell_width),
+            case.width
+        ));
+    }
+    problems
+}
+
+/**
+Read a single case from the command line, for checking a layout that isn't in CASES
+- Unset values use the default window and settings with 100 emojis, the expected grid is worked out from them
+@param args: Command line arguments, without the program name
+@return Result<Option<Case>, String>: The case, None without any layout flags, or which flag was malformed
+*/
+fn case_from_args(args: &[String]) -> Result<Option<Case>, String> {
+    const FLAGS: [&str; 5] = [
+        "--width",
+        "--min-columns",
+        "--max-columns",
+        "--cell-shape",
+        "--emojis",
+    ];
+    if !args.iter().any(|arg| FLAGS.contains(&arg.as_str())) {
+        return Ok(None);
+    }
+
+    let number = |flag: &str, default: usize| match flag_value(args, flag) {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| format!("{} expects a whole number, got {:?}", flag, value)),
+        None => Ok(default),
+    };
+    let defaults = Config::default();
+    let width = match flag_value(args, "--width") {
+        Some(value) => value
+            .parse::<f32>()
+            .ok()
+            .filter(|width| width.is_finite() && *width >= 0.0)
+            .ok_or_else(|| format!("--width expects a size in pixels, got {:?}", value))?,
+        None => WINDOW_WIDTH,
+    };
+    let cell_shape = match flag_value(args, "--cell-shape") {
+        Some("square") | None => CellShape::Square,
+        Some("circle") => CellShape::Circle,
+        Some(other) => {
+            return Err(format!(
+                "--cell-shape expects square or circle, got {:?}",
+                other
+            ));
+        }
+    };
+    let min_columns = number("--min-columns", defaults.min_columns)?;
+    let max_columns = number("--max-columns", defaults.max_columns)?;
+    let emojis = number("--emojis", 100)?;
+
+    let columns = expected_columns(
+        width,
+        min_columns,
+        max_columns,
+        cell_width(EMOJI_SIZE, cell_shape),
+    );
+    Ok(Some(Case {
+        width,
+        min_columns,
+        max_columns,
+        cell_shape,
+        emojis,
+        columns,
+        rows: emojis.div_ceil(columns),
+    }))
+}
+
+/**
+Run every case, or the one given on the command line, and print a pass/fail line for each, then a summary
+@param args: Command line arguments, without the program name
+@return bool: True if every case passed
+*/
+pub fn run(args: &[String]) -> bool {
+    let custom = match case_from_args(args) {
+        Ok(custom) => custom,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let cases = match &custom {
+        Some(case) => std::slice::from_ref(case),
+        None => CASES,
+    };
+
+    let mut failed = 0;
+    for case in cases {
+        let problems = check(case);
+        let label = format!(
+            "{} wide, columns {}..={}, {:?} cells, {} emojis",
+            case.width, case.min_columns, case.max_columns, case.cell_shape, case.emojis
+        );
+        if problems.is_empty() {
+            println!("pass  {}", label);
+        } else {
+            failed += 1;
+            println!("FAIL  {}: {}", label, problems.join(", "));
+        }
+    }
+    println!(
+        "{} of {} layout cases passed",
+        cases.len() - failed,
+        cases.len()
+    );
+    failed == 0
+}
@@ -0,0 +1,584 @@
+use crate::config::SearchScope;
+use crate::data::EmojiData;
+use std::collections::HashMap;
+
+/**
+Score of an emoji found by pasting the glyph itself, above every other kind of match
+*/
+pub const GLYPH_SCORE: u32 = 6;
+
+/**
+Score of an emoji only found through a synonym, below every direct match
+*/
+pub const SYNONYM_SCORE: u32 = 0;
+
+/**
+How a query is matched against the emojis
+*/
+#[derive(Debug, Clone, Copy)]
+pub struct MatchOptions {
+    pub scope: SearchScope,      // Which fields of the emoji are searched
+    pub case_sensitive: bool,    // Whether "Cat" and "cat" are different
+    pub whole_word_below: usize, // Queries shorter than this rank whole words above parts of words
+}
+
+/**
+Searches with fewer results than this may get a category hint, see category_hint()
+*/
+pub const HINT_BELOW_RESULTS: usize = 3;
+
+/**
+Score how well an emoji matches a search query
+- Accents are always ignored, case only when case_sensitive is off
+- Keyword hits rank above category hits
+- Short queries rank whole-word keyword hits above mid-word ones, so "ok" finds 👌 before "book"
+@param query: Search query, expected to be trimmed and non-empty
+@param emoji: Emoji to match against
+@param options: Scope and matching rules
+@return Option<u32>: Match score, higher is better, None if the emoji doesn't match
+*/
+pub fn score(query: &str, emoji: &EmojiData, options: &MatchOptions) -> Option<u32> {
+    let MatchOptions {
+        scope,
+        case_sensitive,
+        whole_word_below,
+    } = *options;
+
+    // Queries like "1f600" or "U+1F600" look up codepoints instead of text
+    if let Some(codepoints) = parse_codepoints(query) {
+        if let Some(score) = codepoint_score(&codepoints, &emoji.emoji) {
+            return Some(score);
+        }
+    }
+
+    let query = normalize(query, case_sensitive);
+    if query.is_empty() {
+        // Nothing but combining marks, which would otherwise match everything
+        return None;
+    }
+
+    if scope.includes_keywords() {
+        let keywords = normalize(&emoji.keywords, case_sensitive);
+        if query.chars().count() < whole_word_below {
+            // Best hit wins, a whole word at the start down to part of a word anywhere
+            let best = keywords
+                .match_indices(&query)
+                .map(|(start, _)| {
+                    match (start == 0, is_whole_word(&keywords, start, query.len())) {
+                        (true, true) => 3,
+                        (false, true) => 2,
+                        _ => 1,
+                    }
+                })
+                .max();
+            if best.is_some() {
+                return best;
+            }
+        } else if keywords.starts_with(&query) {
+            // Matches the start of the primary keyword
+            return Some(3);
+        }
+        if keywords.contains(&query) {
+            return Some(2);
+        }
+    }
+
+    if scope.includes_category() && normalize(&emoji.category, case_sensitive).contains(&query) {
+        return Some(1);
+    }
+
+    None
+}
+
+/**
+Score an emoji against a query with each of its words swapped for their synonyms
+- Only meant for emojis that didn't match the query directly
+@param query: Search query, expected to be trimmed and non-empty
+@param emoji: Emoji to match against
+@param synonyms: Lowercase word to the lowercase words it also stands for
+@param options: Scope and matching rules
+@return Option<u32>: SYNONYM_SCORE if any expansion matches, None otherwise
+*/
+pub fn synonym_score(
+    query: &str,
+    emoji: &EmojiData,
+    synonyms: &HashMap<String, Vec<String>>,
+    options: &MatchOptions,
+) -> Option<u32> {
+    if synonyms.is_empty() {
+        return None;
+    }
+
+    // Synonyms are looked up case-insensitively either way, they're stored lowercase
+    let query = query.to_lowercase();
+    let words: Vec<&str> = query.split_whitespace().collect();
+    for (position, word) in words.iter().enumerate() {
+        for synonym in synonyms.get(*word).into_iter().flatten() {
+            let mut expanded = words.clone();
+            expanded[position] = synonym;
+            if score(&expanded.join(" "), emoji, options).is_some() {
+                return Some(SYNONYM_SCORE);
+            }
+        }
+    }
+    None
+}
+
+/**
+Where the query shows up in the fields of an emoji, for highlighting
+- Empty for fields outside the scope, and for codepoint queries, which match the glyph instead
+@param query: Search query, expected to be trimmed and non-empty
+@param emoji: Emoji to look in
+@param options: Scope and matching rules
+@return (Vec<(usize, usize)>, Vec<(usize, usize)>): Byte ranges in the keywords and in the category
+*/
+pub fn match_spans(
+    query: &str,
+    emoji: &EmojiData,
+    options: &MatchOptions,
+) -> (Vec<(usize, usize)>, Vec<(usize, usize)>) {
+    if parse_codepoints(query).is_some() {
+        return (Vec::new(), Vec::new());
+    }
+    let keywords = if options.scope.includes_keywords() {
+        all_match_spans(query, &emoji.keywords, options.case_sensitive)
+    } else {
+        Vec::new()
+    };
+    let category = if options.scope.includes_category() {
+        all_match_spans(query, &emoji.category, options.case_sensitive)
+    } else {
+        Vec::new()
+    };
+    (keywords, category)
+}
+
+/**
+Find every place a query matches in a text, compared the way score() compares them
+- Overlapping and touching matches are merged, so "aa" in "aaaa" is one span
+@param query: Search query
+@param text: Text to search, e.g. the keywords of an emoji
+@param case_sensitive: Keep the case instead of lowercasing
+@return Vec<(usize, usize)>: Start and end byte offsets into text, sorted and disjoint
+*/
+pub fn all_match_spans(query: &str, text: &str, case_sensitive: bool) -> Vec<(usize, usize)> {
+    let query = normalize(query, case_sensitive);
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    // Normalizing changes lengths, so remember which original character every normalized byte came from
+    let mut folded = String::with_capacity(text.len());
+    let mut origins: Vec<(usize, usize)> = Vec::with_capacity(text.len());
+    for (start, c) in text.char_indices() {
+        let normalized = normalize(c.encode_utf8(&mut [0; 4]), case_sensitive);
+        origins.extend(std::iter::repeat_n(
+            (start, start + c.len_utf8()),
+            normalized.len(),
+        ));
+        folded.push_str(&normalized);
+    }
+
+    let mut spans: Vec<(usize, usize)> = Vec::new();
+    let mut from = 0;
+    while let Some(found) = folded[from..].find(&query) {
+        let start = from + found;
+        let span = (origins[start].0, origins[start + query.len() - 1].1);
+        match spans.last_mut() {
+            Some(last) if span.0 <= last.1 => last.1 = last.1.max(span.1),
+            _ => spans.push(span),
+        }
+        // Step a single character so overlapping matches are found too
+        from = start + folded[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    spans
+}
+
+/**
+Find the category a query probably meant, when the search itself found little
+- Only for searches with fewer than HINT_BELOW_RESULTS results
+- A category within one typo per four letters counts, e.g. "fod" for "Food"
+@param query: Search query, expected to be trimmed
+@param categories: Category names to compare with
+@param results: Number of emojis the search found
+@return Option<&str>: Closest category, None if the search did fine or nothing is close
+*/
+pub fn category_hint<'a>(query: &str, categories: &'a [String], results: usize) -> Option<&'a str> {
+    if query.is_empty() || results >= HINT_BELOW_RESULTS {
+        return None;
+    }
+    let query = normalize(query, false);
+    let allowed = (query.chars().count() / 4).max(1);
+    categories
+        .iter()
+        .map(|category| (edit_distance(&query, &normalize(category, false)), category))
+        .filter(|&(distance, _)| distance <= allowed)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, category)| category.as_str())
+}
+
+/**
+Number of single character insertions, deletions or substitutions that turn one text into another
+@param a: First text
+@param b: Second text
+@return usize: Levenshtein distance, counted in characters
+*/
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // Only the previous row of the table is needed
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/**
+Check if a match stands as a word of its own
+- Words are separated by anything that isn't a letter or digit, e.g. spaces and commas
+@param text: Text the match was found in
+@param start: Byte offset of the match
+@param len: Byte length of the match
+@return bool: True if neither side of the match continues a word
+*/
+fn is_whole_word(text: &str, start: usize, len: usize) -> bool {
+    let before = text[..start].chars().next_back();
+    let after = text[start + len..].chars().next();
+    !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+}
+
+/**
+Bring text into the form queries and keywords are compared in
+- Accented Latin letters lose their accents, so "cafe" finds "café" and the other way round
+- Combining marks are dropped, for text that arrives decomposed
+@param text: Query or field to normalize
+@param case_sensitive: Keep the case instead of lowercasing
+@return String: Normalized text
+*/
+pub fn normalize(text: &str, case_sensitive: bool) -> String {
+    let folded = text
+        .chars()
+        .filter(|c| !('\u{0300}'..='\u{036F}').contains(c))
+        .map(fold_accent);
+    if case_sensitive {
+        folded.collect()
+    } else {
+        folded.flat_map(char::to_lowercase).collect()
+    }
+}
+
+/**
+Strip the accent from a Latin letter, keeping its case
+- Covers Latin-1 and the common Latin Extended-A letters, anything else is returned as is
+@param c: Character to fold
+@return char: The letter without its accent
+*/
+fn fold_accent(c: char) -> char {
+    match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => 'A',
+        'à'..='å' | 'ā' | 'ă' | 'ą' => 'a',
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => 'C',
+        'ç' | 'ć' | 'ĉ' | 'ċ' | 'č' => 'c',
+        'Ď' | 'Đ' => 'D',
+        'ď' | 'đ' => 'd',
+        'È'..='Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => 'E',
+        'è'..='ë' | 'ē' | 'ĕ' | 'ė' | 'ę' | 'ě' => 'e',
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => 'G',
+        'ĝ' | 'ğ' | 'ġ' | 'ģ' => 'g',
+        'Ì'..='Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => 'I',
+        'ì'..='ï' | 'ĩ' | 'ī' | 'ĭ' | 'į' | 'ı' => 'i',
+        'Ł' | 'Ĺ' | 'Ļ' | 'Ľ' => 'L',
+        'ł' | 'ĺ' | 'ļ' | 'ľ' => 'l',
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' => 'N',
+        'ñ' | 'ń' | 'ņ' | 'ň' => 'n',
+        'Ò'..='Ö' | 'Ø' | 'Ō' | 'Ŏ' | 'Ő' => 'O',
+        'ò'..='ö' | 'ø' | 'ō' | 'ŏ' | 'ő' => 'o',
+        'Ŕ' | 'Ŗ' | 'Ř' => 'R',
+        'ŕ' | 'ŗ' | 'ř' => 'r',
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => 'S',
+        'ś' | 'ŝ' | 'ş' | 'š' => 's',
+        'Ţ' | 'Ť' => 'T',
+        'ţ' | 'ť' => 't',
+        'Ù'..='Ü' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => 'U',
+        'ù'..='ü' | 'ũ' | 'ū' | 'ŭ' | 'ů' | 'ű' | 'ų' => 'u',
+        'Ý' | 'Ÿ' => 'Y',
+        'ý' | 'ÿ' => 'y',
+        'Ź' | 'Ż' | 'Ž' => 'Z',
+        'ź' | 'ż' | 'ž' => 'z',
+        _ => c,
+    }
+}
+
+/**
+Parse a query that looks like one or more hexadecimal codepoints
+- Accepts an optional "U+" prefix per codepoint, separated by whitespace
+- Whitespace right after a prefix is ignored, so "U+ 1F600" reads as one codepoint
+- Without a prefix a token needs at least one digit, so words like "face" stay text searches
+@param query: Search query
+@return Option<Vec<u32>>: Parsed codepoints, None if the query doesn't look like codepoints
+*/
+pub fn parse_codepoints(query: &str) -> Option<Vec<u32>> {
+    let joined = join_prefixes(query);
+    let mut codepoints = Vec::new();
+    for token in joined.split_whitespace() {
+        let (hex, prefixed) = match token.get(..2) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("u+") => (&token[2..], true),
+            _ => (token, false),
+        };
+        if hex.is_empty() || hex.len() > 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        if !prefixed && !hex.chars().any(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        codepoints.push(u32::from_str_radix(hex, 16).ok()?);
+    }
+
+    if codepoints.is_empty() {
+        None
+    } else {
+        Some(codepoints)
+    }
+}
+
+/**
+Remove whitespace after every "U+" prefix so the prefix stays with its codepoint
+@param query: Search query
+@return String: Query with prefixes joined to the following token
+*/
+fn join_prefixes(query: &str) -> String {
+    let mut joined = String::with_capacity(query.len());
+    let mut chars = query.chars().peekable();
+    while let Some(c) = chars.next() {
+        joined.push(c);
+        if c == '+' && joined.len() >= 2 && joined[..joined.len() - 1].ends_with(['u', 'U']) {
+            while chars.next_if(|next| next.is_whitespace()).is_some() {}
+        }
+    }
+    joined
+}
+
+/**
+Score an emoji against a list of codepoints
+- Every queried codepoint has to appear in the emoji, an exact sequence ranks highest
+@param codepoints: Queried codepoints
+@param emoji: The emoji glyph
+@return Option<u32>: Match score, None if any codepoint is missing
+*/
+fn codepoint_score(codepoints: &[u32], emoji: &str) -> Option<u32> {
+    let emoji_codepoints: Vec<u32> = emoji.chars().map(|c| c as u32).collect();
+    if emoji_codepoints == codepoints {
+        Some(5)
+    } else if codepoints.iter().all(|c| emoji_codepoints.contains(c)) {
+        Some(4)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Build a dataset entry with just the required fields
+    @param glyph: Emoji glyph
+    @param keywords: Comma separated keywords
+    @param category: Category name
+    @return EmojiData: Entry as if loaded from a data file
+    */
+    fn emoji(glyph: &str, keywords: &str, category: &str) -> EmojiData {
+        serde_json::from_value(serde_json::json!({
+            "emoji": glyph,
+            "keywords": keywords,
+            "category": category,
+        }))
+        .unwrap()
+    }
+
+    /**
+    Default matching rules with the given scope
+    @param scope: Fields to search
+    @return MatchOptions: Case-insensitive options without whole-word ranking
+    */
+    fn options(scope: SearchScope) -> MatchOptions {
+        MatchOptions {
+            scope,
+            case_sensitive: false,
+            whole_word_below: 0,
+        }
+    }
+
+    /**
+    A word only in the keywords is found unless the search is limited to categories
+    */
+    #[test]
+    fn keyword_only_match_respects_scope() {
+        let pizza = emoji("🍕", "pizza, slice", "Food & Drink");
+        assert!(score("slice", &pizza, &options(SearchScope::Keywords)).is_some());
+        assert!(score("slice", &pizza, &options(SearchScope::Both)).is_some());
+        assert_eq!(
+            score("slice", &pizza, &options(SearchScope::Category)),
+            None
+        );
+    }
+
+    /**
+    A word only in the category is found unless the search is limited to keywords
+    */
+    #[test]
+    fn category_only_match_respects_scope() {
+        let pizza = emoji("🍕", "pizza, slice", "Food & Drink");
+        assert!(score("drink", &pizza, &options(SearchScope::Category)).is_some());
+        assert!(score("drink", &pizza, &options(SearchScope::Both)).is_some());
+        assert_eq!(
+            score("drink", &pizza, &options(SearchScope::Keywords)),
+            None
+        );
+    }
+
+    /**
+    Codepoint queries parse with or without a prefix, including a space after it
+    */
+    #[test]
+    fn parse_codepoints_accepts_prefixes() {
+        assert_eq!(parse_codepoints("1F600"), Some(vec![0x1F600]));
+        assert_eq!(parse_codepoints("U+1F600"), Some(vec![0x1F600]));
+        assert_eq!(parse_codepoints("U+ 1F600"), Some(vec![0x1F600]));
+        assert_eq!(
+            parse_codepoints("u+  1f44b U+1F3FD"),
+            Some(vec![0x1F44B, 0x1F3FD])
+        );
+        assert_eq!(parse_codepoints("face"), None);
+        assert_eq!(parse_codepoints("U+"), None);
+    }
+
+    /**
+    A single codepoint finds its emoji and a sequence finds the exact glyph first
+    */
+    #[test]
+    fn codepoint_lookup_finds_emojis() {
+        let grin = emoji("😀", "grinning face", "Smileys & Emotion");
+        let wave = emoji("👋", "waving hand", "People & Body");
+        let wave_tone = emoji("👋🏽", "waving hand: medium skin tone", "People & Body");
+        let scope = options(SearchScope::Both);
+
+        assert!(score("U+ 1F600", &grin, &scope).is_some());
+        assert_eq!(score("U+1F600", &wave, &scope), None);
+
+        let exact = score("1F44B 1F3FD", &wave_tone, &scope).unwrap();
+        assert_eq!(score("1F44B 1F3FD", &wave, &scope), None);
+        assert!(exact > score("1F44B", &wave_tone, &scope).unwrap());
+    }
+
+    /**
+    A concept only in the synonyms still finds the emoji, ranked below any direct match
+    */
+    #[test]
+    fn synonyms_match_below_direct_hits() {
+        let grin = emoji("😁", "beaming face, grin", "Smileys & Emotion");
+        let happy = emoji("🙂", "happy face", "Smileys & Emotion");
+        let synonyms = HashMap::from([(
+            "happy".to_string(),
+            vec!["smile".to_string(), "grin".to_string()],
+        )]);
+        let scope = options(SearchScope::Both);
+
+        assert_eq!(score("happy", &grin, &scope), None);
+        assert_eq!(
+            synonym_score("Happy", &grin, &synonyms, &scope),
+            Some(SYNONYM_SCORE)
+        );
+        assert_eq!(synonym_score("happy", &grin, &HashMap::new(), &scope), None);
+        assert_eq!(synonym_score("sad", &grin, &synonyms, &scope), None);
+
+        let direct = score("happy", &happy, &scope).unwrap();
+        assert!(direct > SYNONYM_SCORE);
+    }
+
+    /**
+    Accents are folded on both sides, case only when the search is case-insensitive
+    */
+    #[test]
+    fn accents_fold_and_case_follows_the_option() {
+        let coffee = emoji("☕", "café, hot beverage", "Food & Drink");
+        let insensitive = options(SearchScope::Both);
+        let sensitive = MatchOptions {
+            case_sensitive: true,
+            ..insensitive
+        };
+
+        assert!(score("cafe", &coffee, &insensitive).is_some());
+        assert!(score("CAFÉ", &coffee, &insensitive).is_some());
+        assert!(score("Cafe", &coffee, &insensitive).is_some());
+        assert!(score("cafe", &coffee, &sensitive).is_some());
+        assert_eq!(score("Cafe", &coffee, &sensitive), None);
+
+        assert_eq!(normalize("Crème Brûlée", false), "creme brulee");
+        assert_eq!(normalize("Crème Brûlée", true), "Creme Brulee");
+    }
+
+    /**
+    Below the threshold "ok" ranks the whole word above "book", at or above it they tie
+    */
+    #[test]
+    fn short_queries_prefer_whole_words() {
+        let ok_hand = emoji("👌", "ok hand, hand", "People & Body");
+        let books = emoji("📚", "books, library", "Objects");
+        let notebook = emoji("📓", "notebook, ok", "Objects");
+        let short = MatchOptions {
+            whole_word_below: 3,
+            ..options(SearchScope::Both)
+        };
+
+        let ok = score("ok", &ok_hand, &short).unwrap();
+        let book = score("ok", &books, &short).unwrap();
+        assert!(ok > book);
+        assert!(score("ok", &notebook, &short).unwrap() > book);
+
+        let off = options(SearchScope::Both);
+        assert!(score("ok", &ok_hand, &off).unwrap() >= score("ok", &books, &off).unwrap());
+        assert_eq!(score("ok", &notebook, &off), score("ok", &books, &off));
+    }
+
+    /**
+    Overlapping and touching matches merge, separate ones stay apart and accents map back to their bytes
+    */
+    #[test]
+    fn all_match_spans_merges_overlaps() {
+        assert_eq!(all_match_spans("aa", "aaaa", false), vec![(0, 4)]);
+        assert_eq!(all_match_spans("ab", "abab", false), vec![(0, 4)]);
+        assert_eq!(
+            all_match_spans("cat", "Cat, bobcat", false),
+            vec![(0, 3), (8, 11)]
+        );
+        assert_eq!(all_match_spans("cat", "Cat, bobcat", true), vec![(8, 11)]);
+        assert_eq!(all_match_spans("cafe", "iced café", false), vec![(5, 10)]);
+        assert!(all_match_spans("", "anything", false).is_empty());
+        assert!(all_match_spans("dog", "cat", false).is_empty());
+    }
+
+    /**
+    A near miss on a category name hints at it, but only when the search itself found little
+    */
+    #[test]
+    fn category_hint_needs_a_close_name_and_few_results() {
+        let categories = ["Food", "Animals", "Flags"].map(String::from);
+        assert_eq!(category_hint("fod", &categories, 0), Some("Food"));
+        assert_eq!(category_hint("ANIMLS", &categories, 2), Some("Animals"));
+        assert_eq!(category_hint("fod", &categories, HINT_BELOW_RESULTS), None);
+        assert_eq!(category_hint("fo", &categories, 0), None);
+        assert_eq!(category_hint("zebra", &categories, 0), None);
+        assert_eq!(category_hint("", &categories, 0), None);
+
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+}
@@ -0,0 +1,572 @@
+use crate::warn;
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Bindings in use, the defaults until install() is called
+static KEYMAP: OnceLock<Keymap> = OnceLock::new();
+
+/**
+Direction the keyboard focus moves in
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+    PageUp, // A screen of rows, or a page in paged mode
+    PageDown,
+    First,
+    Last,
+}
+
+/**
+Actions that can be triggered from the keyboard
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    RandomPick,
+    CycleSortMode,
+    CycleSearchScope,
+    ToggleLayout,
+    UndoCopy,
+    ExportCopyLog,
+    DebugSnapshot,
+    MoveFocus(Direction),
+    ActivateFocus,
+    CopyHovered,
+    MoveFavorite(Direction), // Only in the favorites view, moves the focused favorite
+    ToggleHelp,
+    ToggleLog,
+    OpenSettings,
+    Dismiss,
+    Quit, // Not bound by default, a stray key shouldn't close the window
+}
+
+/**
+Names actions go by in the keybindings config
+*/
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("random", Action::RandomPick),
+    ("cycle_sort", Action::CycleSortMode),
+    ("cycle_scope", Action::CycleSearchScope),
+    ("toggle_layout", Action::ToggleLayout),
+    ("undo_copy", Action::UndoCopy),
+    ("export_copy_log", Action::ExportCopyLog),
+    ("debug_snapshot", Action::DebugSnapshot),
+    ("move_left", Action::MoveFocus(Direction::Left)),
+    ("move_right", Action::MoveFocus(Direction::Right)),
+    ("move_up", Action::MoveFocus(Direction::Up)),
+    ("move_down", Action::MoveFocus(Direction::Down)),
+    ("page_up", Action::MoveFocus(Direction::PageUp)),
+    ("page_down", Action::MoveFocus(Direction::PageDown)),
+    ("first", Action::MoveFocus(Direction::First)),
+    ("last", Action::MoveFocus(Direction::Last)),
+    ("favorite_earlier", Action::MoveFavorite(Direction::Left)),
+    ("favorite_later", Action::MoveFavorite(Direction::Right)),
+    ("copy", Action::ActivateFocus),
+    ("copy_hovered", Action::CopyHovered),
+    ("help", Action::ToggleHelp),
+    ("log", Action::ToggleLog),
+    ("settings", Action::OpenSettings),
+    ("dismiss", Action::Dismiss),
+    ("quit", Action::Quit),
+];
+
+impl Action {
+    /**
+    Look up an action by its config name
+    @param name: Name like "random" or "move_left"
+    @return Option<Action>: The action, None for unknown names
+    */
+    pub fn from_name(name: &str) -> Option<Action> {
+        ACTION_NAMES
+            .iter()
+            .find(|(known, _)| *known == name)
+            .map(|&(_, action)| action)
+    }
+
+    /**
+    What the action does, for the help overlay
+    @param self: Action to describe
+    @return &'static str: Description of its default shortcut
+    */
+    pub fn description(self) -> &'static str {
+        match SHORTCUTS.iter().find(|shortcut| shortcut.action == self) {
+            Some(shortcut) => shortcut.description,
+            None => "Close the window",
+        }
+    }
+}
+
+/**
+A single keyboard shortcut
+*/
+pub struct Shortcut {
+    pub key: Key<&'static str>,
+    pub label: &'static str,       // How the key is shown in the help overlay
+    pub description: &'static str, // What the shortcut does
+    pub action: Action,
+    pub modifiers: Modifiers, // Held with the key, see Binding for how shift is matched
+}
+
+/**
+Every keyboard shortcut, both the key handler and the help overlay are driven from this table
+*/
+pub const SHORTCUTS: &[Shortcut] = &[
+    Shortcut {
+        key: Key::Character("?"),
+        label: "?",
+        description: "Show or hide this help",
+        action: Action::ToggleHelp,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::F1),
+        label: "F1",
+        description: "Show or hide this help",
+        action: Action::ToggleHelp,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::Escape),
+        label: "Esc",
+        description: "Close help or a menu, clear the compare panel, selection and search, then close",
+        action: Action::Dismiss,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("r"),
+        label: "R",
+        description: "Copy a random emoji",
+        action: Action::RandomPick,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("o"),
+        label: "O",
+        description: "Cycle grid ordering",
+        action: Action::CycleSortMode,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("m"),
+        label: "M",
+        description: "Cycle search scope (keywords, category, both)",
+        action: Action::CycleSearchScope,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("l"),
+        label: "L",
+        description: "Switch between grid and list layout",
+        action: Action::ToggleLayout,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("t"),
+        label: "T",
+        description: "Open the theme settings",
+        action: Action::OpenSettings,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("e"),
+        label: "E",
+        description: "Copy a timestamped list of everything copied this session",
+        action: Action::ExportCopyLog,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::F9),
+        label: "F9",
+        description: "Show or hide the log viewer",
+        action: Action::ToggleLog,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::F12),
+        label: "F12",
+        description: "Save what's on screen to debug-snapshot.json, for bug reports",
+        action: Action::DebugSnapshot,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Character("u"),
+        label: "U",
+        description: "Undo the last copy, putting back what the clipboard held before",
+        action: Action::UndoCopy,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowLeft),
+        label: "Left",
+        description: "Move the focus to the previous emoji",
+        action: Action::MoveFocus(Direction::Left),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowRight),
+        label: "Right",
+        description: "Move the focus to the next emoji",
+        action: Action::MoveFocus(Direction::Right),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowUp),
+        label: "Up",
+        description: "Move the focus up a row",
+        action: Action::MoveFocus(Direction::Up),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowDown),
+        label: "Down",
+        description: "Move the focus down a row",
+        action: Action::MoveFocus(Direction::Down),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::Enter),
+        label: "Enter",
+        description: "Copy the focused emoji",
+        action: Action::ActivateFocus,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::Space),
+        label: "Space",
+        description: "Copy the emoji under the cursor, or the focused one",
+        action: Action::CopyHovered,
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::PageDown),
+        label: "PgDn",
+        description: "Move the focus down a screen, or to the next page",
+        action: Action::MoveFocus(Direction::PageDown),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::PageUp),
+        label: "PgUp",
+        description: "Move the focus up a screen, or to the previous page",
+        action: Action::MoveFocus(Direction::PageUp),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::Home),
+        label: "Home",
+        description: "Move the focus to the first emoji",
+        action: Action::MoveFocus(Direction::First),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::End),
+        label: "End",
+        description: "Move the focus to the last emoji",
+        action: Action::MoveFocus(Direction::Last),
+        modifiers: Modifiers::empty(),
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowLeft),
+        label: "Ctrl+Left",
+        description: "Move the focused favorite one place earlier",
+        action: Action::MoveFavorite(Direction::Left),
+        modifiers: Modifiers::CTRL,
+    },
+    Shortcut {
+        key: Key::Named(Named::ArrowRight),
+        label: "Ctrl+Right",
+        description: "Move the focused favorite one place later",
+        action: Action::MoveFavorite(Direction::Right),
+        modifiers: Modifiers::CTRL,
+    },
+];
+
+/**
+Key names accepted in the keybindings config, besides single characters and F1 to F12
+*/
+const KEY_NAMES: &[(&str, Named)] = &[
+    ("escape", Named::Escape),
+    ("esc", Named::Escape),
+    ("enter", Named::Enter),
+    ("return", Named::Enter),
+    ("space", Named::Space),
+    ("tab", Named::Tab),
+    ("backspace", Named::Backspace),
+    ("delete", Named::Delete),
+    ("insert", Named::Insert),
+    ("home", Named::Home),
+    ("end", Named::End),
+    ("pageup", Named::PageUp),
+    ("pgup", Named::PageUp),
+    ("pagedown", Named::PageDown),
+    ("pgdn", Named::PageDown),
+    ("left", Named::ArrowLeft),
+    ("right", Named::ArrowRight),
+    ("up", Named::ArrowUp),
+    ("down", Named::ArrowDown),
+];
+
+/**
+Function keys by number, F1 first
+*/
+const FUNCTION_KEYS: [Named; 12] = [
+    Named::F1,
+    Named::F2,
+    Named::F3,
+    Named::F4,
+    Named::F5,
+    Named::F6,
+    Named::F7,
+    Named::F8,
+    Named::F9,
+    Named::F10,
+    Named::F11,
+    Named::F12,
+];
+
+/**
+Key part of a binding, owned so it can come from the config
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BoundKey {
+    Named(Named),
+    Character(String), // Lowercase, matched regardless of case
+}
+
+/**
+A key with the modifiers that have to be held for it
+- Shift only has to be held if the binding asks for it, so "?" works on layouts where it needs shift
+*/
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binding {
+    key: BoundKey,
+    modifiers: Modifiers,
+}
+
+impl Binding {
+    /**
+    Parse a key combo like "ctrl+shift+k", "F5" or "PageDown"
+    - Names are case-insensitive, modifiers are ctrl, alt, shift and super
+    @param combo: Combo text
+    @return Result<Binding, String>: The binding, or what's wrong with the text
+    */
+    pub fn parse(combo: &str) -> Result<Binding, String> {
+        let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+        // A trailing empty part means the key itself is "+"
+        let (key, modifiers) = match parts.split_last() {
+            Some((&"", [modifiers @ .., ""])) => ("+", modifiers),
+            Some((key, modifiers)) => (*key, modifiers),
+            None => return Err("empty key combo".to_string()),
+        };
+
+        let mut held = Modifiers::empty();
+        for modifier in modifiers {
+            held |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CTRL,
+                "alt" => Modifiers::ALT,
+                "shift" => Modifiers::SHIFT,
+                "super" | "logo" | "cmd" | "meta" => Modifiers::LOGO,
+                other => return Err(format!("unknown modifier {:?}", other)),
+            };
+        }
+
+        let lower = key.to_lowercase();
+        let key = if let Some(&(_, named)) = KEY_NAMES.iter().find(|(name, _)| *name == lower) {
+            BoundKey::Named(named)
+        } else if let Some(number) = lower
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<usize>().ok())
+        {
+            match FUNCTION_KEYS.get(number.wrapping_sub(1)) {
+                Some(&named) => BoundKey::Named(named),
+                None => return Err(format!("no function key {:?}", key)),
+            }
+        } else if lower.chars().count() == 1 {
+            BoundKey::Character(lower)
+        } else {
+            return Err(format!("unknown key {:?}", key));
+        };
+        Ok(Binding {
+            key,
+            modifiers: held,
+        })
+    }
+
+    /**
+    Binding for one of the built-in shortcuts
+    @param shortcut: The shortcut
+    @return Binding: The binding
+    */
+    fn from_shortcut(shortcut: &Shortcut) -> Binding {
+        let key = match &shortcut.key {
+            Key::Named(named) => BoundKey::Named(*named),
+            Key::Character(c) => BoundKey::Character(c.to_lowercase()),
+            Key::Unidentified => BoundKey::Character(String::new()),
+        };
+        Binding {
+            key,
+            modifiers: shortcut.modifiers,
+        }
+    }
+
+    /**
+    Check if a key press triggers this binding
+    @param &self: Self reference
+    @param key: The key that was pressed
+    @param modifiers: Modifier keys held during the press
+    @return bool: True if the binding fires
+    */
+    fn matches(&self, key: Key<&str>, modifiers: Modifiers) -> bool {
+        let key_matches = match (&self.key, key) {
+            (BoundKey::Named(bound), Key::Named(pressed)) => *bound == pressed,
+            (BoundKey::Character(bound), Key::Character(pressed)) => {
+                *bound == pressed.to_lowercase()
+            }
+            _ => false,
+        };
+        key_matches
+            && modifiers.control() == self.modifiers.control()
+            && modifiers.alt() == self.modifiers.alt()
+            && modifiers.logo() == self.modifiers.logo()
+            && (modifiers.shift() || !self.modifiers.shift())
+    }
+
+    /**
+    Number of modifiers the binding needs, more specific bindings are tried first
+    @param &self: Self reference
+    @return u32: Modifier count
+    */
+    fn specificity(&self) -> u32 {
+        self.modifiers.bits().count_ones()
+    }
+}
+
+/**
+A binding in the keymap
+*/
+#[derive(Debug, Clone)]
+pub struct Bound {
+    pub binding: Binding,
+    pub label: String, // How the combo is shown in the help overlay
+    pub action: Action,
+}
+
+/**
+Every key binding in use, the built-in shortcuts with the configured ones laid over them
+*/
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    bindings: Vec<Bound>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap {
+            bindings: SHORTCUTS
+                .iter()
+                .map(|shortcut| Bound {
+                    binding: Binding::from_shortcut(shortcut),
+                    label: shortcut.label.to_string(),
+                    action: shortcut.action,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Keymap {
+    /**
+    Build the keymap from the keybindings config
+    - An action listed in the config loses its built-in keys, an empty list unbinds it
+    - Unknown actions, unparseable combos and conflicts are warned about, a conflicting combo goes to the configured action
+    @param config: Action name to the key combos that trigger it
+    @return Keymap: Keymap to install
+    */
+    pub fn new(config: &HashMap<String, Vec<String>>) -> Keymap {
+        let mut keymap = Keymap::default();
+
+        // Sorted so conflicts are resolved the same way on every run
+        let mut entries: Vec<(&String, &Vec<String>)> = config.iter().collect();
+        entries.sort();
+        for (name, combos) in entries {
+            let Some(action) = Action::from_name(name) else {
+                warn!("Unknown action {:?} in keybindings, ignoring it", name);
+                continue;
+            };
+            keymap.bindings.retain(|bound| bound.action != action);
+            for combo in combos {
+                let binding = match Binding::parse(combo) {
+                    Ok(binding) => binding,
+                    Err(e) => {
+                        warn!("Can't bind {:?} to {}: {}", combo, name, e);
+                        continue;
+                    }
+                };
+                if let Some(taken) = keymap.bindings.iter().find(|b| b.binding == binding) {
+                    warn!(
+                        "{:?} is bound to both {:?} and {}, using {}",
+                        combo, taken.action, name, name
+                    );
+                }
+                keymap.bindings.retain(|bound| bound.binding != binding);
+                keymap.bindings.push(Bound {
+                    binding,
+                    label: combo.trim().to_string(),
+                    action,
+                });
+            }
+        }
+
+        keymap
+            .bindings
+            .sort_by_key(|bound| std::cmp::Reverse(bound.binding.specificity()));
+        keymap
+    }
+
+    /**
+    Look up the action bound to a key press
+    @param &self: Self reference
+    @param key: The key that was pressed
+    @param modifiers: Modifier keys held during the press
+    @return Option<Action>: Bound action, if any
+    */
+    pub fn action_for(&self, key: Key<&str>, modifiers: Modifiers) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|bound| bound.binding.matches(key.clone(), modifiers))
+            .map(|bound| bound.action)
+    }
+
+    /**
+    Every binding, for the help overlay
+    @param &self: Self reference
+    @return &[Bound]: Bindings, most specific first
+    */
+    pub fn bindings(&self) -> &[Bound] {
+        &self.bindings
+    }
+}
+
+/**
+Make a keymap the one key presses are looked up in
+- Only the first call has an effect, key presses before it use the built-in shortcuts
+@param keymap: Keymap to use from now on
+*/
+pub fn install(keymap: Keymap) {
+    if KEYMAP.set(keymap).is_err() {
+        warn!("Keymap already installed, ignoring the new one");
+    }
+}
+
+/**
+Keymap key presses are looked up in
+@return &Keymap: The installed keymap, or the built-in shortcuts
+*/
+pub fn keymap() -> &'static Keymap {
+    KEYMAP.get_or_init(Keymap::default)
+}
@@ -0,0 +1,164 @@
+/**
+Fitzpatrick skin tone modifiers
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkinTone {
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+/**
+Codepoint ranges of the emojis a skin tone modifier can follow (Emoji_Modifier_Base)
+*/
+const MODIFIER_BASES: &[(u32, u32)] = &[
+    (0x261D, 0x261D),
+    (0x26F9, 0x26F9),
+    (0x270A, 0x270D),
+    (0x1F385, 0x1F385),
+    (0x1F3C2, 0x1F3C4),
+    (0x1F3C7, 0x1F3C7),
+    (0x1F3CA, 0x1F3CC),
+    (0x1F442, 0x1F443),
+    (0x1F446, 0x1F450),
+    (0x1F466, 0x1F478),
+    (0x1F47C, 0x1F47C),
+    (0x1F481, 0x1F483),
+    (0x1F485, 0x1F487),
+    (0x1F48F, 0x1F48F),
+    (0x1F491, 0x1F491),
+    (0x1F4AA, 0x1F4AA),
+    (0x1F574, 0x1F575),
+    (0x1F57A, 0x1F57A),
+    (0x1F590, 0x1F590),
+    (0x1F595, 0x1F596),
+    (0x1F645, 0x1F647),
+    (0x1F64B, 0x1F64F),
+    (0x1F6A3, 0x1F6A3),
+    (0x1F6B4, 0x1F6B6),
+    (0x1F6C0, 0x1F6C0),
+    (0x1F6CC, 0x1F6CC),
+    (0x1F90C, 0x1F90C),
+    (0x1F90F, 0x1F90F),
+    (0x1F918, 0x1F91F),
+    (0x1F926, 0x1F926),
+    (0x1F930, 0x1F939),
+    (0x1F93C, 0x1F93E),
+    (0x1F977, 0x1F977),
+    (0x1F9B5, 0x1F9B6),
+    (0x1F9B8, 0x1F9B9),
+    (0x1F9BB, 0x1F9BB),
+    (0x1F9CD, 0x1F9CF),
+    (0x1F9D1, 0x1F9DD),
+    (0x1FAC3, 0x1FAC5),
+    (0x1FAF0, 0x1FAF8),
+];
+
+impl SkinTone {
+    /**
+    Every tone, lightest first
+    */
+    pub const ALL: [SkinTone; 5] = [
+        SkinTone::Light,
+        SkinTone::MediumLight,
+        SkinTone::Medium,
+        SkinTone::MediumDark,
+        SkinTone::Dark,
+    ];
+
+    /**
+    Modifier character for this tone
+    @param self: Skin tone
+    @return char: U+1F3FB to U+1F3FF
+    */
+    pub fn modifier(self) -> char {
+        match self {
+            SkinTone::Light => '\u{1F3FB}',
+            SkinTone::MediumLight => '\u{1F3FC}',
+            SkinTone::Medium => '\u{1F3FD}',
+            SkinTone::MediumDark => '\u{1F3FE}',
+            SkinTone::Dark => '\u{1F3FF}',
+        }
+    }
+}
+
+/**
+Check if a character is one of the skin tone modifiers
+@param c: Character to check
+@return bool: True for U+1F3FB to U+1F3FF
+*/
+fn is_modifier(c: char) -> bool {
+    ('\u{1F3FB}'..='\u{1F3FF}').contains(&c)
+}
+
+/**
+Check if an emoji can take a skin tone
+- Only the first character counts, ZWJ sequences like 🧑‍💻 take the tone on their first person
+@param glyph: Emoji glyph
+@return bool: True if a modifier can follow its first character
+*/
+pub fn supports_tones(glyph: &str) -> bool {
+    glyph.chars().next().is_some_and(|c| {
+        let c = c as u32;
+        MODIFIER_BASES
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&c))
+    })
+}
+
+/**
+Give an emoji a skin tone
+- The modifier goes straight after the first character, replacing the presentation selector or tone already there
+- Emojis that can't take a tone are returned unchanged
+@param glyph: Emoji glyph
+@param tone: Tone to apply
+@return String: The toned glyph
+*/
+pub fn apply(glyph: &str, tone: SkinTone) -> String {
+    if !supports_tones(glyph) {
+        return glyph.to_string();
+    }
+    let mut chars = glyph.chars();
+    let mut toned = String::with_capacity(glyph.len() + 4);
+    toned.extend(chars.next());
+    toned.push(tone.modifier());
+    let rest = chars.as_str();
+    let rest = rest
+        .strip_prefix('\u{FE0F}')
+        .unwrap_or(rest)
+        .trim_start_matches(is_modifier);
+    toned.push_str(rest);
+    toned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    The modifier lands after the first character, replacing a selector or an earlier tone
+    */
+    #[test]
+    fn apply_places_the_modifier() {
+        assert_eq!(apply("👋", SkinTone::Medium), "👋\u{1F3FD}");
+        assert_eq!(apply("✌\u{FE0F}", SkinTone::Light), "✌\u{1F3FB}");
+        assert_eq!(apply("👋\u{1F3FB}", SkinTone::Dark), "👋\u{1F3FF}");
+        assert_eq!(
+            apply("🧑\u{200D}💻", SkinTone::MediumDark),
+            "🧑\u{1F3FE}\u{200D}💻"
+        );
+    }
+
+    /**
+    Emojis that can't take a tone come back unchanged and get no swatches
+    */
+    #[test]
+    fn apply_leaves_toneless_emojis_alone() {
+        assert!(!supports_tones("🍕"));
+        assert_eq!(apply("🍕", SkinTone::Dark), "🍕");
+        assert_eq!(apply("", SkinTone::Dark), "");
+        assert!(SkinTone::ALL.iter().all(|&tone| apply("👍", tone) != "👍"));
+    }
+}
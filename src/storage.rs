@@ -0,0 +1,569 @@
+use crate::config::{Layout, ThemeMode};
+use crate::paths;
+use crate::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/**
+Maximum number of recently copied emojis to remember
+*/
+const MAX_RECENTS: usize = 16;
+
+/**
+Most emojis remembered as being copied together with others, the least used are forgotten first
+*/
+const MAX_PAIRED: usize = 256;
+
+/**
+Most partners remembered per emoji, the least used are forgotten first
+*/
+const MAX_PARTNERS: usize = 8;
+
+/**
+How much being the most recent copy is worth, in copies
+- Each step further back in the recents is worth one copy less
+*/
+const RECENCY_WEIGHT: f32 = 4.0;
+
+/**
+Parts of the user data that can be wiped in one go
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearTarget {
+    Recents,
+    Favorites,
+    Tags,
+}
+
+impl ClearTarget {
+    /**
+    Every clearable part, in the order the settings list them
+    */
+    pub const ALL: [ClearTarget; 3] = [
+        ClearTarget::Recents,
+        ClearTarget::Favorites,
+        ClearTarget::Tags,
+    ];
+
+    /**
+    Name of the part, for buttons and questions
+    @param self: Part to name
+    @return &'static str: Lowercase name
+    */
+    pub fn label(self) -> &'static str {
+        match self {
+            ClearTarget::Recents => "recents",
+            ClearTarget::Favorites => "favorites",
+            ClearTarget::Tags => "tags",
+        }
+    }
+}
+
+/**
+Data that persists between runs
+*/
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserData {
+    pub recents: Vec<String>,                         // Most recent first
+    pub favorites: Vec<String>,                       // In the order they were pinned
+    pub usage: HashMap<String, u32>,                  // Copy count per emoji
+    pub last_used: HashMap<String, u64>,              // Unix time of the last copy per emoji
+    pub tags: HashMap<String, HashSet<String>>,       // User tags per emoji
+    pub pairs: HashMap<String, HashMap<String, u32>>, // Times copied together, per emoji and partner
+    pub layout: Option<Layout>, // Last layout toggled to, None to follow the config
+    pub theme: Option<ThemeMode>, // Theme picked in the settings, None to follow the config
+}
+
+impl UserData {
+    /**
+    Load persisted user data, starting fresh if there is none or it can't be parsed
+    @return UserData: Loaded user data
+    */
+    pub fn load() -> Self {
+        let path = user_data_path();
+
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            info!("No user data found at {}", path.display());
+            return UserData::default();
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Failed to parse {}: {}, starting fresh", path.display(), e);
+            UserData::default()
+        })
+    }
+
+    /**
+    Save user data to disk
+    @param &self: Self reference
+    @return std::io::Result<()>: Ok if the data was written
+    */
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = user_data_path();
+        let json = serde_json::to_string_pretty(self)?;
+        write_atomic(&path, json.as_bytes())
+    }
+
+    /**
+    Lay a session's changes over this data, for sessions that started without it
+    - Session recents come first, usage counts add up, favorites and tags are combined
+    - Unpinning or untagging during the session doesn't reach the saved data, it never showed there
+    @param &self: Data saved before the session
+    @param session: Data built up during the session
+    @return UserData: What to save
+    */
+    pub fn merged_with(&self, session: &UserData) -> UserData {
+        let mut merged = self.clone();
+
+        let mut recents = session.recents.clone();
+        recents.extend(
+            self.recents
+                .iter()
+                .filter(|recent| !session.recents.contains(recent))
+                .cloned(),
+        );
+        recents.truncate(MAX_RECENTS);
+        merged.recents = recents;
+
+        for favorite in &session.favorites {
+            if !merged.favorites.contains(favorite) {
+                merged.favorites.push(favorite.clone());
+            }
+        }
+        for (emoji, uses) in &session.usage {
+            *merged.usage.entry(emoji.clone()).or_insert(0) += uses;
+        }
+        for (emoji, &at) in &session.last_used {
+            let last = merged.last_used.entry(emoji.clone()).or_insert(at);
+            *last = (*last).max(at);
+        }
+        for (emoji, tags) in &session.tags {
+            merged
+                .tags
+                .entry(emoji.clone())
+                .or_default()
+                .extend(tags.iter().cloned());
+        }
+        for (emoji, partners) in &session.pairs {
+            for (partner, count) in partners {
+                for _ in 0..*count {
+                    merged.count_pair(emoji, partner);
+                }
+            }
+        }
+        merged.layout = session.layout.or(self.layout);
+        merged.theme = session.theme.or(self.theme);
+        merged
+    }
+
+    /**
+    Record that an emoji was copied
+    @param &mut self: Mutable self reference
+    @param emoji: The copied emoji
+    */
+    pub fn record_copy(&mut self, emoji: &str) {
+        *self.usage.entry(emoji.to_string()).or_insert(0) += 1;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        self.last_used.insert(emoji.to_string(), now);
+
+        // Move the emoji to the front of the recents
+        self.recents.retain(|recent| recent != emoji);
+        self.recents.insert(0, emoji.to_string());
+        self.recents.truncate(MAX_RECENTS);
+    }
+
+    /**
+    Record that two emojis were copied shortly after each other, in both directions
+    @param &mut self: Mutable self reference
+    @param first: The emoji copied first
+    @param second: The emoji copied after it
+    */
+    pub fn record_pair(&mut self, first: &str, second: &str) {
+        if first == second {
+            return;
+        }
+        self.count_pair(first, second);
+        self.count_pair(second, first);
+    }
+
+    /**
+    Count one direction of a pair, forgetting the least used entries to stay bounded
+    @param &mut self: Mutable self reference
+    @param emoji: Emoji whose partners are counted
+    @param partner: Emoji copied together with it
+    */
+    fn count_pair(&mut self, emoji: &str, partner: &str) {
+        if !self.pairs.contains_key(emoji) && self.pairs.len() >= MAX_PAIRED {
+            let least_used = self
+                .pairs
+                .iter()
+                .min_by_key(|(_, partners)| partners.values().sum::<u32>())
+                .map(|(emoji, _)| emoji.clone());
+            if let Some(least_used) = least_used {
+                self.pairs.remove(&least_used);
+            }
+        }
+
+        let partners = self.pairs.entry(emoji.to_string()).or_default();
+        if !partners.contains_key(partner) && partners.len() >= MAX_PARTNERS {
+            let least_used = partners
+                .iter()
+                .min_by_key(|(_, count)| **count)
+                .map(|(partner, _)| partner.clone());
+            if let Some(least_used) = least_used {
+                partners.remove(&least_used);
+            }
+        }
+        *partners.entry(partner.to_string()).or_insert(0) += 1;
+    }
+
+    /**
+    Emojis most often copied together with an emoji
+    @param &self: Self reference
+    @param emoji: The emoji to find partners for
+    @param limit: Most partners to return
+    @return Vec<&str>: Partners, most often copied together first, ties in glyph order
+    */
+    pub fn often_with(&self, emoji: &str, limit: usize) -> Vec<&str> {
+        let Some(partners) = self.pairs.get(emoji) else {
+            return Vec::new();
+        };
+        let mut ranked: Vec<(&String, u32)> = partners
+            .iter()
+            .map(|(partner, count)| (partner, *count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(limit)
+            .map(|(partner, _)| partner.as_str())
+            .collect()
+    }
+
+    /**
+    Pin or unpin an emoji as a favorite
+    @param &mut self: Mutable self reference
+    @param emoji: The emoji to toggle
+    @return bool: True if the emoji is now a favorite
+    */
+    pub fn toggle_favorite(&mut self, emoji: &str) -> bool {
+        if let Some(position) = self.favorites.iter().position(|f| f == emoji) {
+            self.favorites.remove(position);
+            false
+        } else {
+            self.favorites.push(emoji.to_string());
+            true
+        }
+    }
+
+    /**
+    Wipe one part of the user data
+    @param &mut self: Mutable self reference
+    @param target: Part to wipe
+    */
+    pub fn clear(&mut self, target: ClearTarget) {
+        match target {
+            ClearTarget::Recents => self.recents.clear(),
+            ClearTarget::Favorites => self.favorites.clear(),
+            ClearTarget::Tags => self.tags.clear(),
+        }
+    }
+
+    /**
+    Move a favorite to another place in the list, clamped to the ends
+    @param &mut self: Mutable self reference
+    @param from: Position of the favorite to move
+    @param to: Position it should end up at, anything past the end means last
+    @return bool: True if the order changed
+    */
+    pub fn move_favorite(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.favorites.len() {
+            return false;
+        }
+        let to = to.min(self.favorites.len() - 1);
+        if from == to {
+            return false;
+        }
+        let favorite = self.favorites.remove(from);
+        self.favorites.insert(to, favorite);
+        true
+    }
+
+    /**
+    Tag an emoji
+    @param &mut self: Mutable self reference
+    @param emoji: The emoji to tag
+    @param tag: Tag to add, surrounding whitespace is ignored
+    @return bool: True if the tag was new for this emoji
+    */
+    pub fn add_tag(&mut self, emoji: &str, tag: &str) -> bool {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            return false;
+        }
+        self.tags
+            .entry(emoji.to_string())
+            .or_default()
+            .insert(tag.to_string())
+    }
+
+    /**
+    Remove a tag from an emoji, dropping the emoji's entry once it has no tags left
+    @param &mut self: Mutable self reference
+    @param emoji: The tagged emoji
+    @param tag: Tag to remove
+    */
+    pub fn remove_tag(&mut self, emoji: &str, tag: &str) {
+        if let Some(tags) = self.tags.get_mut(emoji) {
+            tags.remove(tag);
+            if tags.is_empty() {
+                self.tags.remove(emoji);
+            }
+        }
+    }
+
+    /**
+    Check whether an emoji carries a tag
+    @param &self: Self reference
+    @param emoji: The emoji to check
+    @param tag: Tag to look for
+    @return bool: True if the emoji is tagged with it
+    */
+    pub fn has_tag(&self, emoji: &str, tag: &str) -> bool {
+        self.tags.get(emoji).is_some_and(|tags| tags.contains(tag))
+    }
+
+    /**
+    Every tag in use
+    @param &self: Self reference
+    @return Vec<String>: Distinct tags, sorted
+    */
+    pub fn all_tags(&self) -> Vec<String> {
+        let mut tags: Vec<String> = self
+            .tags
+            .values()
+            .flatten()
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .cloned()
+            .collect();
+        tags.sort();
+        tags
+    }
+
+    /**
+    Write the tags to a standalone file, so they can travel with a data bundle
+    @param &self: Self reference
+    @param path: File to write
+    @return std::io::Result<()>: Ok if the file was written
+    */
+    pub fn export_tags(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.tags)?;
+        write_atomic(path, json.as_bytes())
+    }
+
+    /**
+    Merge tags from a file written by export_tags into our own
+    @param &mut self: Mutable self reference
+    @param path: File to read
+    @return std::io::Result<usize>: Number of tags that were new
+    */
+    pub fn import_tags(&mut self, path: &Path) -> std::io::Result<usize> {
+        let contents = std::fs::read_to_string(path)?;
+        let imported: HashMap<String, HashSet<String>> = serde_json::from_str(&contents)?;
+        let mut added = 0;
+        for (emoji, tags) in imported {
+            for tag in tags {
+                if self.add_tag(&emoji, &tag) {
+                    added += 1;
+                }
+            }
+        }
+        Ok(added)
+    }
+
+    /**
+    Recents and favorites merged into one list
+    - Favorites stay pinned at the front in their own order, everything else is ranked by blended_score
+    @param &self: Self reference
+    @return Vec<String>: Emoji glyphs, without duplicates
+    */
+    pub fn frequent(&self) -> Vec<String> {
+        let mut ranked: Vec<(&String, f32)> = self
+            .recents
+            .iter()
+            .filter(|recent| !self.favorites.contains(recent))
+            .enumerate()
+            .map(|(position, recent)| {
+                let uses = self.usage.get(recent).copied().unwrap_or(0);
+                (recent, blended_score(Some(position), uses))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        self.favorites
+            .iter()
+            .chain(ranked.into_iter().map(|(emoji, _)| emoji))
+            .cloned()
+            .collect()
+    }
+}
+
+/**
+Score used to rank the merged frequent view, blending recency and usage
+@param recency: Position in the recents, 0 being the most recent, None if not recent
+@param uses: Number of times the emoji was copied
+@return f32: Higher scores rank first
+*/
+pub fn blended_score(recency: Option<usize>, uses: u32) -> f32 {
+    let recency_score = recency.map_or(0.0, |position| {
+        RECENCY_WEIGHT * (MAX_RECENTS - position.min(MAX_RECENTS)) as f32 / MAX_RECENTS as f32
+    });
+    recency_score + uses as f32
+}
+
+/**
+Write a file without ever leaving it half written
+- Writes to a temporary file next to the target, then renames it over the target
+@param path: File to write
+@param contents: Bytes to write
+@return std::io::Result<()>: Ok if the file was replaced
+*/
+pub fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // The temp file must be on the same filesystem for the rename to be atomic
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    std::fs::rename(&temp_path, path)
+}
+
+/**
+Location of the user data file, in the platform's data directory
+@return PathBuf: Path to the user data file
+*/
+fn user_data_path() -> PathBuf {
+    paths::data_dir().join("user_data.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+    Recency adds to the copy count, fading with each step back, and usage can outweigh it
+    */
+    #[test]
+    fn blended_score_mixes_recency_and_usage() {
+        assert_eq!(blended_score(None, 3), 3.0);
+        assert_eq!(blended_score(Some(0), 0), RECENCY_WEIGHT);
+        assert!(blended_score(Some(0), 1) > blended_score(Some(5), 1));
+        assert!(blended_score(Some(MAX_RECENTS + 10), 2) >= blended_score(None, 2));
+        assert!(blended_score(Some(5), 10) > blended_score(Some(0), 1));
+    }
+
+    /**
+    The merged view keeps favorites first in their order and ranks the other recents by score
+    */
+    #[test]
+    fn frequent_pins_favorites_and_ranks_recents() {
+        let mut data = UserData {
+            recents: ["🍕", "😀", "🎉", "👍"].map(String::from).to_vec(),
+            favorites: ["👍", "❤️"].map(String::from).to_vec(),
+            ..UserData::default()
+        };
+        data.usage.insert("🎉".to_string(), 20);
+        data.usage.insert("😀".to_string(), 1);
+
+        assert_eq!(data.frequent(), ["👍", "❤️", "🎉", "😀", "🍕"]);
+    }
+
+    /**
+    Partners rank by how often they were copied together, both ways, ties in glyph order
+    */
+    #[test]
+    fn often_with_ranks_partners() {
+        let mut data = UserData::default();
+        for _ in 0..3 {
+            data.record_pair("☕", "🥐");
+        }
+        data.record_pair("🥯", "☕");
+        data.record_pair("☕", "🍩");
+        data.record_pair("☕", "☕");
+
+        assert_eq!(data.often_with("☕", 2), ["🥐", "🍩"]);
+        assert_eq!(data.often_with("☕", 10), ["🥐", "🍩", "🥯"]);
+        assert_eq!(data.often_with("🥐", 10), ["☕"]);
+        assert!(data.often_with("🍕", 10).is_empty());
+    }
+
+    /**
+    An emoji never keeps more than MAX_PARTNERS partners
+    */
+    #[test]
+    fn often_with_stays_bounded() {
+        let mut data = UserData::default();
+        data.record_pair("☕", "🥐");
+        data.record_pair("☕", "🥐");
+        for partner in ["🍩", "🥯", "🧇", "🥞", "🍪", "🍰", "🧁", "🥧", "🍫", "🍬"]
+        {
+            data.record_pair("☕", partner);
+        }
+
+        assert_eq!(data.pairs["☕"].len(), MAX_PARTNERS);
+        assert_eq!(data.often_with("☕", 1), ["🥐"]);
+    }
+
+    /**
+    Empty scratch directory for one test, unique to the test and the process
+    @param name: Test name
+    @return PathBuf: Directory that doesn't exist yet
+    */
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("nicepick-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    /**
+    The helper creates missing directories, replaces old contents and leaves no temp file behind
+    */
+    #[test]
+    fn write_atomic_replaces_contents() {
+        let dir = scratch_dir("write-atomic");
+        let path = dir.join("nested").join("user_data.json");
+
+        write_atomic(&path, b"first").unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"first");
+
+        write_atomic(&path, b"second, longer than the first").unwrap();
+        assert_eq!(
+            std::fs::read(&path).unwrap(),
+            b"second, longer than the first"
+        );
+
+        let leftovers: Vec<_> = std::fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers, ["user_data.json"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
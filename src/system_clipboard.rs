@@ -0,0 +1,93 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/**
+Which of the system's selections to write
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Clipboard, // Pasted with ctrl+v
+    Primary,   // Pasted with middle-click, X11 and Wayland only
+}
+
+/**
+Command that reads text on stdin and puts it in a selection
+- wl-copy on Wayland and xclip on X11, both of which keep serving the selection after we exit
+@param target: Selection to write
+@return Command: Command to spawn, not started yet
+*/
+#[cfg(not(target_os = "macos"))]
+fn copy_command(target: Target) -> Command {
+    let primary = target == Target::Primary;
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let mut command = Command::new("wl-copy");
+        if primary {
+            command.arg("--primary");
+        }
+        command
+    } else {
+        let mut command = Command::new("xclip");
+        command.args(["-selection", if primary { "primary" } else { "clipboard" }]);
+        command
+    }
+}
+
+/**
+Command that reads text on stdin and puts it on the clipboard
+- macOS has no primary selection, so both targets end up on the clipboard
+@param _target: Selection to write
+@return Command: Command to spawn, not started yet
+*/
+#[cfg(target_os = "macos")]
+fn copy_command(_target: Target) -> Command {
+    Command::new("pbcopy")
+}
+
+/**
+Write text to a selection without going through Iced, for when there's no window
+- Blocks until the copy tool has taken the text
+@param contents: Text to put in the selection
+@param target: Selection to write
+@return Result<(), String>: Why the text couldn't be copied
+*/
+pub fn write(contents: &str, target: Target) -> Result<(), String> {
+    let mut command = copy_command(target);
+    let mut child = command
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("failed to start {:?}: {}", command, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(contents.as_bytes())
+            .map_err(|e| format!("failed to write to {:?}: {}", command, e))?;
+    }
+    let status = child
+        .wait()
+        .map_err(|e| format!("failed to wait for {:?}: {}", command, e))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{:?} exited with {}", command, status))
+    }
+}
+
+/**
+Write text to the PRIMARY selection, the one middle-click pastes on X11 and Wayland
+- Iced's clipboard only reaches CLIPBOARD, so this goes through the copy tool
+@param contents: Text to put in the selection
+@return Result<(), String>: Why the text couldn't be copied
+*/
+#[cfg(feature = "primary-selection")]
+pub fn write_primary(contents: &str) -> Result<(), String> {
+    write(contents, Target::Primary)
+}
+
+/**
+The primary selection needs the primary-selection feature
+@param _contents: Text that would have been written
+@return Result<(), String>: Always the missing feature
+*/
+#[cfg(not(feature = "primary-selection"))]
+pub fn write_primary(_contents: &str) -> Result<(), String> {
+    Err("nicepick was built without the primary-selection feature".to_string())
+}
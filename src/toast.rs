@@ -0,0 +1,76 @@
+use iced::Color;
+use iced::theme::Palette;
+use std::time::{Duration, Instant};
+
+/**
+How long a toast stays on screen
+*/
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
+/**
+Most toasts shown at once, older ones make way for new ones
+*/
+pub const MAX_TOASTS: usize = 3;
+
+/**
+How important a toast is, which decides its color
+*/
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastLevel {
+    Info,
+    Success, // Something the user asked for worked, e.g. a copy
+    Error,
+}
+
+impl ToastLevel {
+    /**
+    Background color for toasts of this level
+    @param self: Toast level
+    @param palette: Palette of the current theme
+    @return Color: Theme color matching the level
+    */
+    pub fn color(self, palette: &Palette) -> Color {
+        match self {
+            ToastLevel::Info => palette.primary,
+            ToastLevel::Success => palette.success,
+            ToastLevel::Error => palette.danger,
+        }
+    }
+}
+
+/**
+Short lived message shown on top of the grid
+*/
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub text: String,
+    pub level: ToastLevel,
+    expires: Instant,
+}
+
+impl Toast {
+    /**
+    Create a toast that expires after the standard duration
+    @param text: Message to show
+    @param level: How important the message is
+    @param now: Current time
+    @return Toast: New toast
+    */
+    pub fn new(text: String, level: ToastLevel, now: Instant) -> Self {
+        Toast {
+            text,
+            level,
+            expires: now + TOAST_DURATION,
+        }
+    }
+
+    /**
+    Check if the toast should be taken down
+    @param &self: Self reference
+    @param now: Current time
+    @return bool: True once the toast has been shown long enough
+    */
+    pub fn expired(&self, now: Instant) -> bool {
+        now >= self.expires
+    }
+}